@@ -2,6 +2,7 @@ use std::fs::File;
 use std::io::prelude::*;
 use std::io::BufReader;
 
+#[allow(dead_code)]
 const DIGIT_NAMES: [&str; 9] = [
     "one",
     "two",
@@ -47,6 +48,7 @@ fn main() -> anyhow::Result<()> {
 mod test {
     #[cfg(test)]
     mod tests {
+        #[allow(unused_imports)]
         use super::*;
 
         #[test]
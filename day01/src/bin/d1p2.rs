@@ -28,7 +28,7 @@ where
 }
 
 fn find_first_digit(line: &str) -> Option<(usize, usize)> {
-    for (i, c) in line.chars().enumerate() {
+    for (i, c) in line.char_indices() {
         if c.is_ascii_digit() {
             let num = c.to_digit(10).unwrap();
             return Some((i, num as usize));
@@ -86,6 +86,7 @@ fn main() -> anyhow::Result<()> {
 mod test {
     #[cfg(test)]
     mod tests {
+        #[allow(unused_imports)]
         use super::*;
 
         #[test]
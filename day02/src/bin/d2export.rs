@@ -0,0 +1,50 @@
+use std::str::FromStr;
+use clap::Parser;
+
+use day02::diagnostics::ParseOptions;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+enum Format {
+    #[default] Json,
+    Csv,
+    Text,
+}
+
+impl FromStr for Format {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(Format::Json),
+            "csv" => Ok(Format::Csv),
+            "text" => Ok(Format::Text),
+            _ => Err(anyhow::anyhow!("Invalid format")),
+        }
+    }
+}
+
+#[derive(Debug, Parser)]
+struct Args {
+    #[arg(default_value = "input.txt")]
+    filename: String,
+    #[command(flatten)]
+    parse: ParseOptions,
+    #[arg(long)]
+    format: Option<Format>,
+}
+
+fn main() -> anyhow::Result<()> {
+    let args = Args::parse();
+    let games = args.parse.load(&args.filename)?;
+    let stdout = std::io::stdout().lock();
+    match args.format.unwrap_or_default() {
+        Format::Json => day02::write_json(&games, stdout)?,
+        Format::Csv => day02::write_csv(&games, stdout)?,
+        Format::Text => {
+            for game in games {
+                println!("{game}");
+            }
+        }
+    }
+    Ok(())
+}
@@ -1,46 +1,29 @@
-use std::fs::File;
-use std::io::prelude::*;
-use std::io::BufReader;
-use day02::{Color, Game};
+use clap::Parser;
+use day02::diagnostics::ParseOptions;
+use day02::{Color, DieSet};
 
-fn filter(game: &Game) -> bool {
-    for pick in game.picks.iter() {
-        match pick.color {
-            Color::Red => {
-                if pick.count > 12 {
-                    return false;
-                }
-            }
-            Color::Green => {
-                if pick.count > 13 {
-                    return false;
-                }
-            }
-            Color::Blue => {
-                if pick.count > 14 {
-                    return false;
-                }
-            }
-        }
-    }
-    true
+fn bag() -> DieSet {
+    [(Color::RED, 12), (Color::GREEN, 13), (Color::BLUE, 14)]
+        .into_iter()
+        .collect()
+}
+
+#[derive(Debug, Parser)]
+struct Args {
+    #[arg(default_value = "input.txt")]
+    filename: String,
+    #[command(flatten)]
+    parse: ParseOptions,
 }
 
 fn main() -> anyhow::Result<()> {
-    let filename = std::env::args()
-        .nth(1)
-        .unwrap_or_else(|| "input.txt".to_string());
-    let f = File::open(filename)?;
-    let reader = BufReader::new(f);
+    let args = Args::parse();
+    let games = args.parse.load(&args.filename)?;
+    let bag = bag();
     let mut sum = 0;
-    for line in reader.lines() {
-        let line = line?.trim().to_string();
-        if line.is_empty() {
-            continue;
-        }
-        let game = Game::try_from(line.as_str())?;
-        if filter(&game) {
-            println!("{line}");
+    for game in games {
+        if game.is_possible(&bag) {
+            println!("{game}");
             //println!("{:#?}", game);
             sum += game.id;
         }
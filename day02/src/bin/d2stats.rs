@@ -0,0 +1,64 @@
+use clap::Parser;
+
+use day02::stats::{inconsistent_games, Estimator, MAX_PER_COLOR};
+use day02::diagnostics::ParseOptions;
+use day02::DieSet;
+
+#[derive(Debug, Parser)]
+struct Args {
+    #[arg(default_value = "input.txt")]
+    filename: String,
+    #[command(flatten)]
+    parse: ParseOptions,
+    /// The claimed bag contents to check every game against
+    #[arg(long, default_value = "12 red, 13 green, 14 blue")]
+    bag: DieSet,
+    /// Largest number of cubes of a single color considered possible
+    #[arg(long, default_value_t = 30, value_parser = clap::value_parser!(u32).range(..=MAX_PER_COLOR as i64))]
+    max: u32,
+    /// Probability mass inside the reported credible intervals
+    #[arg(long, default_value_t = 0.9)]
+    credibility: f64,
+    /// Report games with a p-value below this threshold
+    #[arg(long, default_value_t = 0.05)]
+    alpha: f64,
+}
+
+fn main() -> anyhow::Result<()> {
+    let args = Args::parse();
+    let games = args.parse.load(&args.filename)?;
+
+    let estimator = Estimator { max_per_color: args.max, credibility: args.credibility };
+    let interval = format!("{:.0}% interval", args.credibility * 100.0);
+    println!("{:>5} {:<10} {:>4} {:>6} {:>14}", "game", "color", "mle", "mean", interval);
+    for game in &games {
+        let estimates = match estimator.estimate(game) {
+            Ok(estimates) => estimates,
+            Err(err) => {
+                println!("{:>5} skipped: {err}", game.id);
+                continue;
+            }
+        };
+        for estimate in estimates {
+            println!(
+                "{:>5} {:<10} {:>4} {:>6.2} {:>14}",
+                game.id,
+                estimate.color,
+                estimate.mle,
+                estimate.mean,
+                format!("{}..={}", estimate.lower, estimate.upper),
+            );
+        }
+    }
+
+    println!();
+    println!("games inconsistent with {} (alpha = {}):", args.bag, args.alpha);
+    for report in inconsistent_games(&games, &args.bag, args.alpha) {
+        if report.is_impossible() {
+            println!("{:>5} impossible", report.id);
+        } else {
+            println!("{:>5} p = {:.4}", report.id, report.p_value);
+        }
+    }
+    Ok(())
+}
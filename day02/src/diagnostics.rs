@@ -0,0 +1,218 @@
+//! Reporting malformed lines in a games file.
+//!
+//! Every line is parsed even after a failure so that all problems in a file can be
+//! shown at once, each pointing at the offending part of its line.
+use std::fmt;
+
+use pest::error::{ErrorVariant, InputLocation};
+
+use crate::{Game, Rule};
+
+/// Describe a grammar rule the way it should read in an error message.
+fn describe(rule: &Rule) -> &'static str {
+    match rule {
+        Rule::game => "a game like `Game 1: 3 blue, 4 red`",
+        Rule::game_number => "a game number",
+        Rule::color => "a color name",
+        Rule::count => "a cube count",
+        Rule::set => "a round of picks",
+        Rule::pick => "a pick like `3 blue`",
+        Rule::bag => "a bag like `12 red, 13 green`",
+        Rule::WHITESPACE => "a space",
+        Rule::EOI => "the end of the line",
+    }
+}
+
+/// Join descriptions as `a`, `a or b` or `a, b or c`.
+fn one_of(rules: &[Rule]) -> String {
+    let mut names: Vec<&str> = rules.iter().map(describe).collect();
+    names.dedup();
+    match names.split_last() {
+        None => String::new(),
+        Some((last, [])) => last.to_string(),
+        Some((last, rest)) => format!("{} or {last}", rest.join(", ")),
+    }
+}
+
+/// A problem with a single line of input, rendered with the line underlined.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub path: Option<String>,
+    /// One based line number within the file.
+    pub line: usize,
+    /// One based character column where the problem starts.
+    pub column: usize,
+    /// Number of characters to underline, at least one.
+    pub width: usize,
+    pub source: String,
+    pub message: String,
+}
+
+impl Diagnostic {
+    /// Build a diagnostic for `source`, which was line `line` of the input.
+    pub fn new(line: usize, source: &str, error: &anyhow::Error) -> Self {
+        let Some(error) = error.downcast_ref::<pest::error::Error<Rule>>() else {
+            return Diagnostic {
+                path: None,
+                line,
+                column: 1,
+                width: source.chars().count().max(1),
+                source: source.to_string(),
+                message: error.to_string(),
+            };
+        };
+        let (start, end) = match error.location {
+            InputLocation::Pos(pos) => (pos, pos),
+            InputLocation::Span(span) => span,
+        };
+        let column = source[..start].chars().count() + 1;
+        let found = source[start..]
+            .split_whitespace()
+            .next()
+            .map(|word| format!("found `{word}`"))
+            .unwrap_or_else(|| "found the end of the line".to_string());
+        let width = match end > start {
+            true => source[start..end].chars().count(),
+            false => source[start..].split_whitespace().next().map_or(1, |w| w.chars().count()),
+        };
+        let message = match &error.variant {
+            ErrorVariant::ParsingError { positives, negatives } if positives.is_empty() => {
+                format!("unexpected {}, {found}", one_of(negatives))
+            }
+            ErrorVariant::ParsingError { positives, .. } => {
+                format!("expected {}, {found}", one_of(positives))
+            }
+            ErrorVariant::CustomError { message } => message.clone(),
+        };
+        Diagnostic {
+            path: None,
+            line,
+            column,
+            width,
+            source: source.to_string(),
+            message,
+        }
+    }
+
+    pub fn with_path(mut self, path: &str) -> Self {
+        self.path = Some(path.to_string());
+        self
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let gutter = " ".repeat(self.line.to_string().len());
+        writeln!(f, "error: {}", self.message)?;
+        let path = self.path.as_deref().unwrap_or("<input>");
+        writeln!(f, "{gutter}--> {path}:{}:{}", self.line, self.column)?;
+        writeln!(f, "{gutter} |")?;
+        writeln!(f, "{} | {}", self.line, self.source)?;
+        write!(
+            f,
+            "{gutter} | {}{}",
+            " ".repeat(self.column - 1),
+            "^".repeat(self.width),
+        )
+    }
+}
+
+/// Parse every line of `input`, returning the games that parsed and a diagnostic
+/// for each line that did not. Blank lines at the end of the input are ignored.
+pub fn parse_games(input: &str) -> (Vec<Game>, Vec<Diagnostic>) {
+    let mut games = Vec::new();
+    let mut diagnostics = Vec::new();
+    let lines: Vec<&str> = input.lines().collect();
+    let last = lines.iter().rposition(|line| !line.trim().is_empty()).map_or(0, |i| i + 1);
+    for (i, line) in lines[..last].iter().enumerate() {
+        let line = line.trim_end_matches('\r');
+        match Game::try_from(line) {
+            Ok(game) => games.push(game),
+            Err(err) => diagnostics.push(Diagnostic::new(i + 1, line, &err)),
+        }
+    }
+    (games, diagnostics)
+}
+
+/// Command line flags controlling what happens to malformed lines.
+#[derive(Debug, Clone, Copy, Default, clap::Args)]
+// Keep the doc comment above out of every binary's --help.
+#[command(about = None, long_about = None)]
+pub struct ParseOptions {
+    /// Abort if any line fails to parse (the default)
+    #[arg(long, conflicts_with = "lenient")]
+    pub strict: bool,
+    /// Skip lines that fail to parse after reporting them
+    #[arg(long)]
+    pub lenient: bool,
+}
+
+impl ParseOptions {
+    /// Whether malformed lines abort, which they do unless `--lenient` is given.
+    pub fn is_strict(&self) -> bool {
+        self.strict || !self.lenient
+    }
+
+    /// Read and parse the games in `path`, printing every diagnostic to stderr.
+    pub fn load(&self, path: &str) -> anyhow::Result<Vec<Game>> {
+        let input = std::fs::read_to_string(path)?;
+        let (games, diagnostics) = parse_games(&input);
+        for diagnostic in &diagnostics {
+            eprintln!("{}\n", diagnostic.clone().with_path(path));
+        }
+        if self.is_strict() && !diagnostics.is_empty() {
+            anyhow::bail!("{} malformed lines in {path}", diagnostics.len());
+        }
+        Ok(games)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_collects_every_bad_line() {
+        let input = "Game 1: 3 blue\nGame two: 3 blue\nGame 3: 4 red\n\nGame 5: 3 blue 4\n\n";
+        let (games, diagnostics) = parse_games(input);
+        assert_eq!(games.len(), 2);
+        let lines: Vec<usize> = diagnostics.iter().map(|d| d.line).collect();
+        assert_eq!(lines, vec![2, 4, 5]);
+    }
+
+    #[test]
+    fn test_render() {
+        let (_, diagnostics) = parse_games("Game 1: 3 blue\nGame two: 3 blue");
+        let rendered = diagnostics[0].clone().with_path("input.txt").to_string();
+        assert_eq!(
+            rendered,
+            "error: expected a game number, found `two:`\n \
+             --> input.txt:2:6\n  |\n\
+             2 | Game two: 3 blue\n  |      ^^^^",
+        );
+    }
+
+    #[derive(clap::Parser)]
+    struct Cli {
+        #[command(flatten)]
+        parse: ParseOptions,
+    }
+
+    #[test]
+    fn test_options() {
+        use clap::Parser;
+        let parse = |args: &[&str]| Cli::try_parse_from([&["d2"], args].concat()).map(|cli| cli.parse);
+        assert!(parse(&[]).unwrap().is_strict());
+        assert!(parse(&["--strict"]).unwrap().is_strict());
+        assert!(!parse(&["--lenient"]).unwrap().is_strict());
+        assert!(parse(&["--strict", "--lenient"]).is_err());
+    }
+
+    #[test]
+    fn test_count_overflow_span() {
+        let (_, diagnostics) = parse_games("Game 1: 4294967296 red");
+        assert_eq!(diagnostics[0].column, 9);
+        assert_eq!(diagnostics[0].width, 10);
+        assert!(diagnostics[0].message.contains("u32"));
+    }
+}
@@ -1,73 +1,278 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::Mutex;
+
+use lazy_static::lazy_static;
 use pest_derive::Parser;
+use pest::error::ErrorVariant;
+use pest::iterators::Pair;
 use pest::Parser;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
+pub mod diagnostics;
+pub mod stats;
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
-pub enum Color {
-    Red,
-    Green,
-    Blue,
+lazy_static! {
+    /// Every color name seen so far. A `Color` is an index into this list, so the
+    /// first three entries line up with the `Color::RED`, `GREEN` and `BLUE` constants.
+    static ref COLORS: Mutex<Vec<&'static str>> = Mutex::new(vec!["red", "green", "blue"]);
 }
 
-impl TryFrom<&str> for Color {
-    type Error = anyhow::Error;
+/// An interned cube color. Any identifier accepted by the grammar is a valid color.
+/// Colors order by name, whatever order they were interned in.
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Color(usize);
 
-    fn try_from(value: &str) -> Result<Self, Self::Error> {
-        match value {
-            "red" => Ok(Color::Red),
-            "green" => Ok(Color::Green),
-            "blue" => Ok(Color::Blue),
-            _ => Err(anyhow::anyhow!("Invalid color: {value}")),
+impl Color {
+    pub const RED: Color = Color(0);
+    pub const GREEN: Color = Color(1);
+    pub const BLUE: Color = Color(2);
+
+    /// Look up a color by name, registering it if it has not been seen before.
+    pub fn intern(name: &str) -> Color {
+        let mut colors = COLORS.lock().unwrap();
+        if let Some(index) = colors.iter().position(|c| *c == name) {
+            return Color(index);
         }
+        colors.push(Box::leak(name.to_string().into_boxed_str()));
+        Color(colors.len() - 1)
+    }
+
+    pub fn name(&self) -> &'static str {
+        COLORS.lock().unwrap()[self.0]
+    }
+}
+
+impl PartialOrd for Color {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Color {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        if self.0 == other.0 {
+            return std::cmp::Ordering::Equal;
+        }
+        self.name().cmp(other.name())
+    }
+}
+
+impl From<&str> for Color {
+    fn from(value: &str) -> Self {
+        Color::intern(value)
+    }
+}
+
+impl fmt::Debug for Color {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Color({})", self.name())
     }
 }
 
-#[derive(Debug)]
-pub struct DieSet {
-    red: u8,
-    green: u8,
-    blue: u8,
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(self.name())
+    }
+}
+
+impl Serialize for Color {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name())
+    }
 }
 
-#[derive(Debug)]
+impl<'de> Deserialize<'de> for Color {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        Ok(Color::intern(&name))
+    }
+}
+
+/// A count of cubes per color. Colors that were never inserted count as zero.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DieSet(BTreeMap<Color, u32>);
+
+impl DieSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, color: Color) -> u32 {
+        self.0.get(&color).copied().unwrap_or(0)
+    }
+
+    pub fn insert(&mut self, color: Color, count: u32) {
+        self.0.insert(color, count);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Color, u32)> + '_ {
+        self.0.iter().map(|(color, count)| (*color, *count))
+    }
+
+    /// True if every color in `other` is available in at least the same amount.
+    pub fn contains(&self, other: &DieSet) -> bool {
+        other.iter().all(|(color, count)| count <= self.get(color))
+    }
+
+    /// Product of the counts of red, green and blue, which count even when
+    /// missing and then make the power zero, and of every other color in the set.
+    pub fn power(&self) -> u64 {
+        let standard = [Color::RED, Color::GREEN, Color::BLUE];
+        let others = self.iter().filter(|(color, _)| !standard.contains(color));
+        standard
+            .iter()
+            .map(|color| self.get(*color))
+            .chain(others.map(|(_, count)| count))
+            .map(|count| count as u64)
+            .product()
+    }
+}
+
+impl fmt::Display for DieSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, (color, count)) in self.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            write!(f, "{count} {color}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Parses the same `12 red, 13 green` format as a single round.
+impl std::str::FromStr for DieSet {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut set = DieSet::new();
+        let bag = GameParser::parse(Rule::bag, s.trim())?;
+        for pick in bag.flatten().filter(|pair| pair.as_rule() == Rule::pick) {
+            let mut inner = pick.into_inner();
+            let count = parse_number(&inner.next().unwrap(), "count", "a u32")?;
+            let color = Color::intern(inner.next().unwrap().as_str());
+            set.insert(color, count);
+        }
+        Ok(set)
+    }
+}
+
+impl<C: Into<Color>> FromIterator<(C, u32)> for DieSet {
+    fn from_iter<T: IntoIterator<Item = (C, u32)>>(iter: T) -> Self {
+        DieSet(iter.into_iter().map(|(color, count)| (color.into(), count)).collect())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Pick {
     pub color: Color,
-    pub count: u8,
+    pub count: u32,
+}
+
+impl fmt::Display for Pick {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.count, self.color)
+    }
+}
+
+/// The picks revealed together in a single handful, between two `;` in the input.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Round {
+    pub picks: Vec<Pick>,
 }
 
-#[derive(Debug)]
+impl Round {
+    pub fn set(&self) -> DieSet {
+        self.picks.iter().map(|pick| (pick.color, pick.count)).collect()
+    }
+
+    pub fn total(&self) -> u32 {
+        self.picks.iter().map(|pick| pick.count).sum()
+    }
+}
+
+impl fmt::Display for Round {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, pick) in self.picks.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            write!(f, "{pick}")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Game {
     pub id: usize,
-    pub picks: Vec<Pick>,
+    pub rounds: Vec<Round>,
+}
+
+impl Game {
+    /// Every pick in the game regardless of which round it was revealed in.
+    pub fn picks(&self) -> impl Iterator<Item = &Pick> {
+        self.rounds.iter().flat_map(|round| round.picks.iter())
+    }
+
+    /// The fewest cubes of each color that make every pick in the game possible.
+    pub fn min_set(&self) -> DieSet {
+        let mut set = DieSet::new();
+        for pick in self.picks() {
+            if pick.count > set.get(pick.color) {
+                set.insert(pick.color, pick.count);
+            }
+        }
+        set
+    }
+
+    pub fn is_possible(&self, bag: &DieSet) -> bool {
+        bag.contains(&self.min_set())
+    }
 }
 
 #[derive(Parser)]
 #[grammar = "game.pest"]
 pub struct GameParser;
 
+/// Parse the digits matched by `pair`, reporting overflow as an error on its span.
+fn parse_number<T: std::str::FromStr>(
+    pair: &Pair<Rule>,
+    what: &str,
+    kind: &str,
+) -> anyhow::Result<T> {
+    pair.as_str().parse().map_err(|_| {
+        let message = format!("Invalid {what} {}, must fit in {kind}", pair.as_str());
+        let variant = ErrorVariant::<Rule>::CustomError { message };
+        anyhow::Error::from(pest::error::Error::new_from_span(variant, pair.as_span()))
+    })
+}
+
 impl TryFrom<&str> for Game {
     type Error = anyhow::Error;
 
     fn try_from(value: &str) -> Result<Self, Self::Error> {
         let mut id = 0;
-        let mut picks = Vec::new();
+        let mut rounds = Vec::new();
         let parsed_game = GameParser::parse(Rule::game, value)?;
         for pair in parsed_game {
             for inner in pair.into_inner() {
                 match inner.as_rule() {
                     Rule::game_number => {
-                        id = inner.as_str().parse()?;
+                        id = parse_number(&inner, "game number", "a usize")?;
                     }
                     Rule::set => {
+                        let mut picks = Vec::new();
                         let mut color_ptr: Option<Color> = None;
-                        let mut count_ptr: Option<u8> = None;
+                        let mut count_ptr: Option<u32> = None;
                         for thing in inner.into_inner().flatten() {
                             match thing.as_rule() {
                                 Rule::color => {
-                                    color_ptr = Some(Color::try_from(thing.as_str())?);
+                                    color_ptr = Some(Color::intern(thing.as_str()));
                                 }
                                 Rule::count => {
-                                    count_ptr = Some(thing.as_str().trim().parse()?);
+                                    count_ptr = Some(parse_number(&thing, "count", "a u32")?);
                                 }
                                 Rule::pick => continue,
                                 _ => unreachable!(),
@@ -81,11 +286,156 @@ impl TryFrom<&str> for Game {
                                 count_ptr = None;
                             }
                         }
+                        rounds.push(Round { picks });
                     }
+                    Rule::EOI => continue,
                     _ => unreachable!(),
                 }
             }
         }
-        Ok(Game { id, picks })
+        Ok(Game { id, rounds })
+    }
+}
+
+impl fmt::Display for Game {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Game {}: ", self.id)?;
+        for (i, round) in self.rounds.iter().enumerate() {
+            if i > 0 {
+                f.write_str("; ")?;
+            }
+            write!(f, "{round}")?;
+        }
+        Ok(())
+    }
+}
+
+/// One row of the CSV export, a single pick along with where it was revealed.
+#[derive(Debug, Serialize)]
+struct PickRecord {
+    game: usize,
+    round: usize,
+    color: Color,
+    count: u32,
+}
+
+pub fn write_json<W: std::io::Write>(games: &[Game], writer: W) -> anyhow::Result<()> {
+    serde_json::to_writer_pretty(writer, games)?;
+    Ok(())
+}
+
+/// Write one row per pick with the game id and the zero based round index.
+pub fn write_csv<W: std::io::Write>(games: &[Game], writer: W) -> anyhow::Result<()> {
+    let mut writer = csv::Writer::from_writer(writer);
+    for game in games {
+        for (round, picks) in game.rounds.iter().enumerate() {
+            for pick in &picks.picks {
+                writer.serialize(PickRecord {
+                    game: game.id,
+                    round,
+                    color: pick.color,
+                    count: pick.count,
+                })?;
+            }
+        }
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    #[allow(unused_imports)]
+    use super::*;
+
+    const EXAMPLE: &str = include_str!("../example.txt");
+
+    #[test]
+    fn test_example_min_sets() {
+        let powers: Vec<u64> = EXAMPLE.lines()
+            .filter(|line| !line.is_empty())
+            .map(|line| Game::try_from(line).unwrap().min_set().power())
+            .collect();
+        assert_eq!(powers, vec![48, 12, 1560, 630, 36]);
+    }
+
+    #[test]
+    fn test_power_missing_colors() {
+        let game = Game::try_from("Game 1: 3 red, 4 blue; 2 red").unwrap();
+        assert_eq!(game.min_set().power(), 0);
+        let set: DieSet = [("red", 2), ("green", 3), ("blue", 4), ("yellow", 5)].into_iter().collect();
+        assert_eq!(set.power(), 120);
+        let set: DieSet = [("yellow", 5)].into_iter().collect();
+        assert_eq!(set.power(), 0);
+    }
+
+    #[test]
+    fn test_color_order() {
+        let (zebra, apple) = (Color::from("zebra"), Color::from("apple"));
+        assert!(apple < zebra);
+        assert!(Color::BLUE < Color::GREEN && Color::GREEN < Color::RED);
+        let set: DieSet = [(zebra, 1), (Color::RED, 2), (apple, 3)].into_iter().collect();
+        assert_eq!(set.to_string(), "3 apple, 2 red, 1 zebra");
+    }
+
+    #[test]
+    fn test_custom_colors() {
+        let game = Game::try_from("Game 7: 3 yellow, 1 purple; 300 yellow").unwrap();
+        let min_set = game.min_set();
+        assert_eq!(min_set.get(Color::from("yellow")), 300);
+        assert_eq!(min_set.get(Color::from("purple")), 1);
+        assert_eq!(min_set.get(Color::RED), 0);
+        let bag: DieSet = [("yellow", 300), ("purple", 2)].into_iter().collect();
+        assert!(game.is_possible(&bag));
+        let bag: DieSet = [("yellow", 299), ("purple", 2)].into_iter().collect();
+        assert!(!game.is_possible(&bag));
+    }
+
+    #[test]
+    fn test_count_overflow() {
+        assert!(Game::try_from("Game 1: 4294967295 red").is_ok());
+        let err = Game::try_from("Game 1: 4294967296 red").unwrap_err();
+        assert!(err.to_string().contains("4294967296"));
+    }
+
+    #[test]
+    fn test_die_set_from_str() {
+        let bag: DieSet = "12 red, 13 green, 14 blue".parse().unwrap();
+        assert_eq!(bag.get(Color::GREEN), 13);
+        assert_eq!(bag.to_string(), "14 blue, 13 green, 12 red");
+        assert!("12 red; 13 green".parse::<DieSet>().is_err());
+    }
+
+    #[test]
+    fn test_rounds() {
+        let game = Game::try_from("Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red").unwrap();
+        assert_eq!(game.rounds.len(), 3);
+        assert_eq!(game.rounds[1].set().get(Color::GREEN), 13);
+        assert_eq!(game.rounds[2].total(), 6);
+        assert_eq!(game.picks().count(), 8);
+    }
+
+    #[test]
+    fn test_display_round_trip() {
+        for line in EXAMPLE.lines().filter(|line| !line.is_empty()) {
+            let game = Game::try_from(line).unwrap();
+            assert_eq!(game.to_string(), line);
+            assert_eq!(Game::try_from(game.to_string().as_str()).unwrap(), game);
+        }
+    }
+
+    #[test]
+    fn test_export() {
+        let games = vec![Game::try_from("Game 2: 1 blue, 2 green; 3 green").unwrap()];
+        let mut json = Vec::new();
+        write_json(&games, &mut json).unwrap();
+        let parsed: Vec<Game> = serde_json::from_slice(&json).unwrap();
+        assert_eq!(parsed, games);
+        let mut csv = Vec::new();
+        write_csv(&games, &mut csv).unwrap();
+        assert_eq!(
+            String::from_utf8(csv).unwrap(),
+            "game,round,color,count\n2,0,blue,1\n2,0,green,2\n2,1,green,3\n",
+        );
     }
 }
@@ -0,0 +1,367 @@
+//! Estimating the contents of the bag from the rounds of a game.
+//!
+//! Each round is modeled as a handful drawn without replacement from the bag and
+//! returned before the next round, so the counts in a round follow a multivariate
+//! hypergeometric distribution whose handful size is the round total.
+use crate::{Color, DieSet, Game, Round};
+
+/// Refuse to evaluate more bag compositions than this for a single game.
+const MAX_GRID: usize = 20_000_000;
+
+/// The largest `max_per_color` an [`Estimator`] accepts, which bounds the table
+/// of log factorials it builds.
+pub const MAX_PER_COLOR: u32 = 10_000;
+
+/// Natural log of `n!` for every `n` up to a fixed size.
+struct LnFactorial(Vec<f64>);
+
+impl LnFactorial {
+    fn new(max: usize) -> Self {
+        let mut table = Vec::with_capacity(max + 1);
+        table.push(0.0);
+        for n in 1..=max {
+            table.push(table[n - 1] + (n as f64).ln());
+        }
+        LnFactorial(table)
+    }
+
+    /// Log of the binomial coefficient, negative infinity when `k > n`.
+    fn choose(&self, n: u32, k: u32) -> f64 {
+        if k > n {
+            return f64::NEG_INFINITY;
+        }
+        let (n, k) = (n as usize, k as usize);
+        self.0[n] - self.0[k] - self.0[n - k]
+    }
+}
+
+/// Point estimates and a credible interval for the number of cubes of one color.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Estimate {
+    pub color: Color,
+    /// The count in the most likely bag over the searched grid.
+    pub mle: u32,
+    /// Mean of the marginal posterior.
+    pub mean: f64,
+    pub lower: u32,
+    pub upper: u32,
+}
+
+/// Grid search over bag compositions with a uniform prior on `0..=max_per_color`
+/// for every color seen in the game.
+///
+/// Larger bags always look more like sampling with replacement, so the likelihood
+/// tends to grow with the bag and `max_per_color` is what keeps the estimate finite.
+#[derive(Debug, Clone, Copy)]
+pub struct Estimator {
+    pub max_per_color: u32,
+    /// Probability mass inside the reported interval, e.g. `0.9`.
+    pub credibility: f64,
+}
+
+impl Default for Estimator {
+    fn default() -> Self {
+        Estimator { max_per_color: 30, credibility: 0.9 }
+    }
+}
+
+impl Estimator {
+    pub fn estimate(&self, game: &Game) -> anyhow::Result<Vec<Estimate>> {
+        let min_set = game.min_set();
+        let colors: Vec<Color> = min_set.iter().map(|(color, _)| color).collect();
+        if colors.is_empty() {
+            return Ok(Vec::new());
+        }
+        if self.max_per_color > MAX_PER_COLOR {
+            anyhow::bail!("A maximum of {} cubes per color is more than {MAX_PER_COLOR}", self.max_per_color);
+        }
+        let lows: Vec<u32> = min_set.iter().map(|(_, low)| low).collect();
+        if let Some(low) = lows.iter().find(|low| **low > self.max_per_color) {
+            anyhow::bail!(
+                "Game {} draws {low} cubes of one color, more than the maximum of {}",
+                game.id,
+                self.max_per_color,
+            );
+        }
+        lows
+            .iter()
+            .try_fold(1_usize, |acc, low| {
+                let choices = self.max_per_color.checked_sub(*low)?.checked_add(1)?;
+                acc.checked_mul(usize::try_from(choices).ok()?)
+            })
+            .filter(|size| *size <= MAX_GRID)
+            .ok_or_else(|| anyhow::anyhow!(
+                "Game {} has too many bag compositions to search, lower the maximum per color",
+                game.id,
+            ))?;
+
+        let largest_bag = (self.max_per_color as usize)
+            .checked_mul(colors.len())
+            .ok_or_else(|| anyhow::anyhow!("Game {} has too many colors to search", game.id))?;
+        let ln_fact = LnFactorial::new(largest_bag);
+        // The log likelihood separates into a term per color and a term for the bag size.
+        let color_terms: Vec<Vec<f64>> = colors
+            .iter()
+            .map(|color| {
+                (0..=self.max_per_color)
+                    .map(|n| {
+                        game.rounds
+                            .iter()
+                            .map(|round| ln_fact.choose(n, round.set().get(*color)))
+                            .sum()
+                    })
+                    .collect()
+            })
+            .collect();
+        let totals: Vec<u32> = game.rounds.iter().map(Round::total).collect();
+        let size_term = |size: u32| -> f64 {
+            totals.iter().map(|k| ln_fact.choose(size, *k)).sum()
+        };
+
+        let ll = |bag: &[u32]| -> f64 {
+            let size: u32 = bag.iter().sum();
+            bag.iter()
+                .zip(color_terms.iter())
+                .map(|(n, terms)| terms[*n as usize])
+                .sum::<f64>()
+                - size_term(size)
+        };
+
+        // The first pass finds the most likely bag, so that the second can weigh
+        // every bag relative to it without underflowing.
+        let mut bag = lows.clone();
+        let mut best = (f64::NEG_INFINITY, bag.clone());
+        loop {
+            let ll = ll(&bag);
+            if ll > best.0 {
+                best.0 = ll;
+                best.1.copy_from_slice(&bag);
+            }
+            if !self.advance(&mut bag, &lows) {
+                break;
+            }
+        }
+
+        let max_ll = best.0;
+        let mut marginals = vec![vec![0.0; self.max_per_color as usize + 1]; colors.len()];
+        let mut total = 0.0;
+        bag.copy_from_slice(&lows);
+        loop {
+            let weight = (ll(&bag) - max_ll).exp();
+            total += weight;
+            for (marginal, n) in marginals.iter_mut().zip(&bag) {
+                marginal[*n as usize] += weight;
+            }
+            if !self.advance(&mut bag, &lows) {
+                break;
+            }
+        }
+
+        let tail = (1.0 - self.credibility) / 2.0;
+        let estimates = colors
+            .into_iter()
+            .zip(marginals)
+            .zip(best.1)
+            .map(|((color, marginal), mle)| {
+                let mut mean = 0.0;
+                let mut cumulative = 0.0;
+                let mut lower = None;
+                let mut upper = None;
+                for (n, weight) in marginal.iter().enumerate() {
+                    let p = weight / total;
+                    mean += n as f64 * p;
+                    cumulative += p;
+                    if lower.is_none() && cumulative >= tail {
+                        lower = Some(n as u32);
+                    }
+                    if upper.is_none() && cumulative >= 1.0 - tail - 1e-12 {
+                        upper = Some(n as u32);
+                    }
+                }
+                Estimate {
+                    color,
+                    mle,
+                    mean,
+                    lower: lower.unwrap_or(self.max_per_color),
+                    upper: upper.unwrap_or(self.max_per_color),
+                }
+            })
+            .collect();
+        Ok(estimates)
+    }
+
+    /// Step `bag` to the next composition, counting every color up from its
+    /// low like an odometer. False once every composition has been visited.
+    fn advance(&self, bag: &mut [u32], lows: &[u32]) -> bool {
+        for (n, low) in bag.iter_mut().zip(lows) {
+            if *n < self.max_per_color {
+                *n += 1;
+                return true;
+            }
+            *n = *low;
+        }
+        false
+    }
+}
+
+/// Probability of a round at least as unlikely as the observed one if it had been
+/// drawn from `bag`, by enumerating every handful of the same size.
+pub fn round_p_value(round: &Round, bag: &DieSet) -> f64 {
+    let observed = round.set();
+    if !bag.contains(&observed) {
+        return 0.0;
+    }
+    let counts: Vec<(u32, u32)> = bag
+        .iter()
+        .map(|(color, n)| (n, observed.get(color)))
+        .collect();
+    let size: u32 = counts.iter().map(|(n, _)| n).sum();
+    let handful = round.total();
+    let ln_fact = LnFactorial::new(size as usize);
+    let ln_total = ln_fact.choose(size, handful);
+    let ln_prob = |draw: &[u32]| -> f64 {
+        counts
+            .iter()
+            .zip(draw)
+            .map(|((n, _), k)| ln_fact.choose(*n, *k))
+            .sum::<f64>()
+            - ln_total
+    };
+    let observed_draw: Vec<u32> = counts.iter().map(|(_, k)| *k).collect();
+    let threshold = ln_prob(&observed_draw) + 1e-9;
+
+    fn visit(
+        counts: &[(u32, u32)],
+        remaining: u32,
+        draw: &mut Vec<u32>,
+        f: &mut impl FnMut(&[u32]),
+    ) {
+        if draw.len() == counts.len() {
+            if remaining == 0 {
+                f(draw);
+            }
+            return;
+        }
+        let (n, _) = counts[draw.len()];
+        for k in 0..=n.min(remaining) {
+            draw.push(k);
+            visit(counts, remaining - k, draw, f);
+            draw.pop();
+        }
+    }
+
+    let mut p_value = 0.0;
+    visit(&counts, handful, &mut Vec::new(), &mut |draw| {
+        let ln_p = ln_prob(draw);
+        if ln_p <= threshold {
+            p_value += ln_p.exp();
+        }
+    });
+    p_value.min(1.0)
+}
+
+/// Combine the rounds of a game with a Bonferroni correction on the least likely one.
+pub fn game_p_value(game: &Game, bag: &DieSet) -> f64 {
+    let rounds = game.rounds.len().max(1) as f64;
+    let least = game.rounds
+        .iter()
+        .map(|round| round_p_value(round, bag))
+        .fold(1.0_f64, f64::min);
+    (least * rounds).min(1.0)
+}
+
+/// A game whose draws are unlikely to have come from the claimed bag.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Inconsistency {
+    pub id: usize,
+    pub p_value: f64,
+}
+
+impl Inconsistency {
+    /// True if some round could not have been drawn from the bag at all.
+    pub fn is_impossible(&self) -> bool {
+        self.p_value == 0.0
+    }
+}
+
+/// Every game whose p-value under the claimed bag falls below `alpha`.
+pub fn inconsistent_games(games: &[Game], bag: &DieSet, alpha: f64) -> Vec<Inconsistency> {
+    games
+        .iter()
+        .map(|game| Inconsistency { id: game.id, p_value: game_p_value(game, bag) })
+        .filter(|report| report.p_value < alpha)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn game(line: &str) -> Game {
+        Game::try_from(line).unwrap()
+    }
+
+    #[test]
+    fn test_estimate_bounds() {
+        let game = game("Game 1: 3 red, 1 blue; 2 red, 2 blue; 4 red");
+        let estimator = Estimator { max_per_color: 12, credibility: 0.9 };
+        let estimates = estimator.estimate(&game).unwrap();
+        assert_eq!(estimates.len(), 2);
+        let red = estimates.iter().find(|e| e.color == Color::RED).unwrap();
+        let blue = estimates.iter().find(|e| e.color == Color::BLUE).unwrap();
+        assert!(red.lower >= 4 && red.lower <= red.mle && red.mle <= red.upper);
+        assert!(blue.lower >= 2 && blue.upper <= 12);
+        assert!(red.mean > 4.0 && red.mean < 12.0);
+    }
+
+    #[test]
+    fn test_single_draw_mle_is_tight() {
+        // Drawing everything in the bag is certain only when the bag holds exactly that.
+        let game = game("Game 1: 3 red, 1 blue");
+        let estimates = Estimator { max_per_color: 10, credibility: 0.9 }
+            .estimate(&game)
+            .unwrap();
+        let mle = |color| estimates.iter().find(|e| e.color == color).unwrap().mle;
+        assert_eq!(mle(Color::RED), 3);
+        assert_eq!(mle(Color::BLUE), 1);
+    }
+
+    #[test]
+    fn test_max_per_color_limit() {
+        let game = game("Game 1: 3 red");
+        for max_per_color in [MAX_PER_COLOR + 1, u32::MAX] {
+            let err = Estimator { max_per_color, credibility: 0.9 }.estimate(&game).unwrap_err();
+            assert_eq!(err.to_string(), format!("A maximum of {max_per_color} cubes per color is more than {MAX_PER_COLOR}"));
+        }
+        let estimates = Estimator { max_per_color: MAX_PER_COLOR, credibility: 0.9 }.estimate(&game).unwrap();
+        assert_eq!(estimates[0].mle, 3);
+    }
+
+    #[test]
+    fn test_round_p_value() {
+        let bag: DieSet = [(Color::RED, 2), (Color::BLUE, 2)].into_iter().collect();
+        // Handfuls of two from {r, r, b, b}: rr 1/6, bb 1/6, rb 4/6.
+        let round = &game("Game 1: 2 red").rounds[0];
+        assert!((round_p_value(round, &bag) - 2.0 / 6.0).abs() < 1e-9);
+        let round = &game("Game 1: 1 red, 1 blue").rounds[0];
+        assert!((round_p_value(round, &bag) - 1.0).abs() < 1e-9);
+        let round = &game("Game 1: 3 red").rounds[0];
+        assert_eq!(round_p_value(round, &bag), 0.0);
+    }
+
+    #[test]
+    fn test_inconsistent_games() {
+        let games = vec![
+            game("Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green"),
+            game("Game 2: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green"),
+            game("Game 3: 1 yellow"),
+        ];
+        let bag: DieSet = [(Color::RED, 12), (Color::GREEN, 13), (Color::BLUE, 14)]
+            .into_iter()
+            .collect();
+        let report = inconsistent_games(&games, &bag, 0.05);
+        let ids: Vec<usize> = report.iter().map(|r| r.id).collect();
+        assert_eq!(ids, vec![2, 3]);
+        assert!(report.iter().all(Inconsistency::is_impossible));
+    }
+}
@@ -2,18 +2,11 @@ use std::fs::File;
 use std::io::prelude::*;
 use std::io::BufReader;
 
-use day03::{Point, Schematic};
+use day03::Schematic;
 
 
 fn numbers_with_symbols(schematic: &Schematic) -> Vec<u32> {
-    let mut numbers = schematic.numbers();
-    numbers.retain(|n| {
-        schematic
-            .neighbors(n.offset, n.len())
-            .iter()
-            .any(|&n| matches!(schematic.data[n], Point::Symbol(_)))
-    });
-    numbers.into_iter().map(|n| n.value()).collect()
+    schematic.graph().parts().map(|n| n.value()).collect()
 }
 
 fn main() -> anyhow::Result<()> {
@@ -24,7 +17,7 @@ fn main() -> anyhow::Result<()> {
     let mut reader = BufReader::new(f);
     let mut buffer = String::new();
     reader.read_to_string(&mut buffer)?;
-    let schematic = Schematic::from(buffer.as_str());
+    let schematic = Schematic::try_from(buffer.as_str())?;
     let solution: u32 = numbers_with_symbols(&schematic)
         .into_iter()
         .sum();
@@ -41,7 +34,7 @@ mod tests {
 
     #[test]
     fn example_test() {
-        let schematic = Schematic::from(EXAMPLE);
+        let schematic = Schematic::try_from(EXAMPLE).unwrap();
         let numbers = numbers_with_symbols(&schematic);
         assert_eq!(numbers, vec![467, 35, 633, 617, 592, 755, 664, 598]);
         assert!(!numbers.contains(&58));
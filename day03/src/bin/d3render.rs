@@ -0,0 +1,72 @@
+use std::fs::File;
+use std::io::prelude::*;
+use std::io::BufReader;
+use std::str::FromStr;
+use clap::Parser;
+
+use day03::graph::GearRule;
+use day03::neighborhood::{Neighborhood, Orientation, Shape};
+use day03::render::{Renderer, Window};
+use day03::Schematic;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+enum Format {
+    #[default] Ansi,
+    Html,
+}
+
+impl FromStr for Format {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "ansi" => Ok(Format::Ansi),
+            "html" => Ok(Format::Html),
+            _ => Err(anyhow::anyhow!("Invalid format")),
+        }
+    }
+}
+
+#[derive(Debug, Parser)]
+struct Args {
+    #[arg(default_value = "input.txt")]
+    filename: String,
+    #[arg(long)]
+    format: Option<Format>,
+    /// Only draw the cells in x,y,width,height
+    #[arg(long)]
+    window: Option<Window>,
+    /// von-neumann, moore, chebyshev:R or stencil:dx,dy;dx,dy;...
+    #[arg(long, default_value = "moore")]
+    neighborhood: Shape,
+    /// Wrap neighborhoods around the edges of the schematic
+    #[arg(long)]
+    wrap: bool,
+    /// Read numbers top to bottom instead of left to right
+    #[arg(long)]
+    vertical: bool,
+}
+
+fn main() -> anyhow::Result<()> {
+    let args = Args::parse();
+    let f = File::open(args.filename)?;
+    let mut reader = BufReader::new(f);
+    let mut buffer = String::new();
+    reader.read_to_string(&mut buffer)?;
+    let orientation = match args.vertical {
+        true => Orientation::Vertical,
+        false => Orientation::Horizontal,
+    };
+    let schematic = Schematic::try_from(buffer.as_str())?
+        .with_neighborhood(Neighborhood { shape: args.neighborhood, wrap: args.wrap })
+        .with_orientation(orientation);
+    let mut renderer = Renderer::new(&schematic, &GearRule::STANDARD);
+    if let Some(window) = args.window {
+        renderer = renderer.with_window(window);
+    }
+    match args.format.unwrap_or_default() {
+        Format::Ansi => print!("{}", renderer.ansi()),
+        Format::Html => print!("{}", renderer.html()),
+    }
+    Ok(())
+}
@@ -0,0 +1,217 @@
+//! Adjacency between the numbers of a schematic and its symbols.
+//!
+//! The graph is bipartite: every edge joins a number to a symbol cell touching it.
+//! Numbers and symbols are referred to by their index in `numbers()` and `symbols()`.
+use std::collections::HashMap;
+
+use crate::{Number, Point, Schematic};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Symbol {
+    pub offset: usize,
+    pub char: char,
+}
+
+/// Which symbols count as gears and how many numbers they must touch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GearRule {
+    /// Only symbols with this character, or any symbol if `None`.
+    pub symbol: Option<char>,
+    /// The exact number of adjacent numbers.
+    pub neighbors: usize,
+}
+
+impl GearRule {
+    /// A `*` adjacent to exactly two numbers.
+    pub const STANDARD: GearRule = GearRule { symbol: Some('*'), neighbors: 2 };
+}
+
+/// A set of numbers and symbols reachable from each other through adjacency.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Component {
+    pub numbers: Vec<usize>,
+    pub symbols: Vec<usize>,
+}
+
+#[derive(Debug, Clone)]
+pub struct PartGraph {
+    numbers: Vec<Number>,
+    symbols: Vec<Symbol>,
+    number_edges: Vec<Vec<usize>>,
+    symbol_edges: Vec<Vec<usize>>,
+}
+
+impl PartGraph {
+    pub fn new(schematic: &Schematic) -> Self {
+        let numbers = schematic.numbers();
+        let symbols: Vec<Symbol> = schematic
+            .grid
+            .cells()
+            .iter()
+            .enumerate()
+            .filter_map(|(offset, point)| match point {
+                Point::Symbol(c) => Some(Symbol { offset, char: *c }),
+                _ => None,
+            })
+            .collect();
+        let symbol_index: HashMap<usize, usize> = symbols
+            .iter()
+            .enumerate()
+            .map(|(i, symbol)| (symbol.offset, i))
+            .collect();
+        let mut number_edges = vec![Vec::new(); numbers.len()];
+        let mut symbol_edges = vec![Vec::new(); symbols.len()];
+        for (n, number) in numbers.iter().enumerate() {
+            for offset in schematic.neighbors(number.offset, number.len()) {
+                if let Some(&s) = symbol_index.get(&offset) {
+                    number_edges[n].push(s);
+                    symbol_edges[s].push(n);
+                }
+            }
+        }
+        PartGraph { numbers, symbols, number_edges, symbol_edges }
+    }
+
+    pub fn numbers(&self) -> &[Number] {
+        &self.numbers
+    }
+
+    pub fn symbols(&self) -> &[Symbol] {
+        &self.symbols
+    }
+
+    pub fn symbols_adjacent_to(&self, number: usize) -> impl Iterator<Item = &Symbol> {
+        self.number_edges[number].iter().map(|s| &self.symbols[*s])
+    }
+
+    pub fn numbers_adjacent_to(&self, symbol: usize) -> impl Iterator<Item = &Number> {
+        self.symbol_edges[symbol].iter().map(|n| &self.numbers[*n])
+    }
+
+    /// Numbers adjacent to at least one symbol.
+    pub fn parts(&self) -> impl Iterator<Item = &Number> {
+        self.numbers
+            .iter()
+            .zip(self.number_edges.iter())
+            .filter(|(_, edges)| !edges.is_empty())
+            .map(|(number, _)| number)
+    }
+
+    /// Numbers adjacent to no symbol at all.
+    pub fn isolated(&self) -> impl Iterator<Item = &Number> {
+        self.numbers
+            .iter()
+            .zip(self.number_edges.iter())
+            .filter(|(_, edges)| edges.is_empty())
+            .map(|(number, _)| number)
+    }
+
+    /// Groups of parts that share symbols, directly or through other parts. Numbers
+    /// without any symbol are left out, symbols without any number are included.
+    pub fn components(&self) -> Vec<Component> {
+        let mut seen_symbols = vec![false; self.symbols.len()];
+        let mut seen_numbers = vec![false; self.numbers.len()];
+        let mut components = Vec::new();
+        for start in 0..self.symbols.len() {
+            if seen_symbols[start] {
+                continue;
+            }
+            seen_symbols[start] = true;
+            let mut component = Component { numbers: Vec::new(), symbols: Vec::new() };
+            let mut stack = vec![start];
+            while let Some(symbol) = stack.pop() {
+                component.symbols.push(symbol);
+                for &number in &self.symbol_edges[symbol] {
+                    if seen_numbers[number] {
+                        continue;
+                    }
+                    seen_numbers[number] = true;
+                    component.numbers.push(number);
+                    for &next in &self.number_edges[number] {
+                        if !seen_symbols[next] {
+                            seen_symbols[next] = true;
+                            stack.push(next);
+                        }
+                    }
+                }
+            }
+            component.numbers.sort();
+            component.symbols.sort();
+            components.push(component);
+        }
+        components
+    }
+
+    /// Symbols matching the gear rule.
+    pub fn gears<'a>(&'a self, rule: &'a GearRule) -> impl Iterator<Item = usize> + 'a {
+        (0..self.symbols.len()).filter(move |s| {
+            rule.symbol.is_none_or(|c| self.symbols[*s].char == c)
+                && self.symbol_edges[*s].len() == rule.neighbors
+        })
+    }
+
+    /// Sum over every gear of `combine` applied to the values of its numbers, e.g.
+    /// the product of both numbers for the standard gear ratio.
+    pub fn gear_sum<F>(&self, rule: &GearRule, combine: F) -> u64
+    where
+        F: Fn(&[u32]) -> u64,
+    {
+        self.gears(rule)
+            .map(|s| {
+                let values: Vec<u32> = self.numbers_adjacent_to(s).map(Number::value).collect();
+                combine(&values)
+            })
+            .sum()
+    }
+}
+
+impl Schematic {
+    pub fn graph(&self) -> PartGraph {
+        PartGraph::new(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = include_str!("../example.txt");
+
+    fn product(values: &[u32]) -> u64 {
+        values.iter().map(|v| *v as u64).product()
+    }
+
+    #[test]
+    fn test_adjacency() {
+        let graph = Schematic::try_from(EXAMPLE).unwrap().graph();
+        let isolated: Vec<u32> = graph.isolated().map(Number::value).collect();
+        assert_eq!(isolated, vec![114, 58]);
+        let first: Vec<char> = graph.symbols_adjacent_to(0).map(|s| s.char).collect();
+        assert_eq!(first, vec!['*']);
+        let star = graph.symbols().iter().position(|s| s.char == '*').unwrap();
+        let values: Vec<u32> = graph.numbers_adjacent_to(star).map(Number::value).collect();
+        assert_eq!(values, vec![467, 35]);
+    }
+
+    #[test]
+    fn test_gears() {
+        let graph = Schematic::try_from(EXAMPLE).unwrap().graph();
+        assert_eq!(graph.gears(&GearRule::STANDARD).count(), 2);
+        assert_eq!(graph.gear_sum(&GearRule::STANDARD, product), 467835);
+        let any_single = GearRule { symbol: None, neighbors: 1 };
+        let sum = graph.gear_sum(&any_single, |v| v.iter().map(|v| *v as u64).sum());
+        assert_eq!(sum, 633 + 617 + 592 + 664);
+    }
+
+    #[test]
+    fn test_components() {
+        let graph = Schematic::try_from("1*2#3\n.....\n4$5.6").unwrap().graph();
+        let components = graph.components();
+        let values: Vec<Vec<u32>> = components
+            .iter()
+            .map(|c| c.numbers.iter().map(|n| graph.numbers()[*n].value()).collect())
+            .collect();
+        assert_eq!(values, vec![vec![1, 2, 3], vec![4, 5]]);
+        assert_eq!(components[0].symbols.len(), 2);
+    }
+}
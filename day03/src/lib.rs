@@ -1,4 +1,13 @@
 use std::hash::{Hash, Hasher};
+use std::str::FromStr;
+
+use grid::Grid;
+
+pub mod graph;
+pub mod neighborhood;
+pub mod render;
+
+use neighborhood::{Neighborhood, Orientation};
 
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -13,7 +22,7 @@ impl From<char> for Point {
         match c {
             '0'..='9' => Point::Num(c),
             '.' => Point::Empty,
-            '\n' => panic!("Newline cannot be parsed into a Point"),
+            '\n' | '\r' => panic!("Line endings cannot be parsed into a Point"),
             _ => Point::Symbol(c),
         }
     }
@@ -29,28 +38,78 @@ impl From<Point> for char {
     }
 }
 
+#[derive(Debug, Clone)]
 pub struct Schematic {
-    pub width: usize,
-    pub data: Vec<Point>,
+    pub grid: Grid<Point>,
+    pub neighborhood: Neighborhood,
+    pub orientation: Orientation,
 }
 
-impl From<&str> for Schematic {
-    fn from(s: &str) -> Self {
-        let mut data = Vec::new();
+/// How to treat rows that are shorter than the longest row.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ParseOptions {
+    /// Fill short rows with `Point::Empty` instead of rejecting them.
+    pub pad: bool,
+}
+
+impl Schematic {
+    /// Parse a schematic, accepting `\n`, `\r\n` or `\r` line endings with or
+    /// without a final newline. Errors name the one based row and column at fault.
+    pub fn parse(s: &str, options: ParseOptions) -> anyhow::Result<Self> {
+        let normalized = s.replace("\r\n", "\n").replace('\r', "\n");
+        let mut rows: Vec<&str> = normalized.split('\n').collect();
+        while rows.last().is_some_and(|row| row.is_empty()) {
+            rows.pop();
+        }
+        if rows.is_empty() {
+            anyhow::bail!("Schematic is empty");
+        }
         let mut width = 0;
-        for (i, char) in s.chars().enumerate() {
-            match (width, char) {
-                (0, '\n') => {
-                    width = i;
-                    continue;
-                }
-                (_, '\n') => continue,
-                _ => {
-                    data.push(Point::from(char))
-                }
+        for (y, row) in rows.iter().enumerate() {
+            if row.is_empty() {
+                anyhow::bail!("Row {} is empty", y + 1);
+            }
+            if let Some((x, c)) = row.chars().enumerate().find(|(_, c)| c.is_whitespace()) {
+                anyhow::bail!("Row {}, column {}: unexpected whitespace {c:?}", y + 1, x + 1);
+            }
+            width = width.max(row.chars().count());
+        }
+        let mut cells = Vec::with_capacity(width * rows.len());
+        let expected = rows[0].chars().count();
+        for (y, row) in rows.iter().enumerate() {
+            let length = row.chars().count();
+            if length != expected && !options.pad {
+                anyhow::bail!(
+                    "Row {}, column {}: expected {expected} columns like row 1 but found {length}",
+                    y + 1,
+                    length.min(expected) + 1,
+                );
             }
+            cells.extend(row.chars().map(Point::from));
+            cells.extend(std::iter::repeat_n(Point::Empty, width - length));
         }
-        Schematic { width, data }
+        let grid = Grid::from_vec(width, cells)?;
+        Ok(Schematic {
+            grid,
+            neighborhood: Neighborhood::default(),
+            orientation: Orientation::default(),
+        })
+    }
+}
+
+impl FromStr for Schematic {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Schematic::parse(s, ParseOptions::default())
+    }
+}
+
+impl TryFrom<&str> for Schematic {
+    type Error = anyhow::Error;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        s.parse()
     }
 }
 
@@ -89,58 +148,101 @@ impl Number {
 
 impl Schematic {
 
+    pub fn with_neighborhood(mut self, neighborhood: Neighborhood) -> Self {
+        self.neighborhood = neighborhood;
+        self
+    }
+
+    pub fn with_orientation(mut self, orientation: Orientation) -> Self {
+        self.orientation = orientation;
+        self
+    }
+
+    /// Offsets of the `length` cells of a number starting at `offset`, following
+    /// the schematic's orientation.
+    pub fn run(&self, offset: usize, length: usize) -> Vec<usize> {
+        let step = match self.orientation {
+            Orientation::Horizontal => 1,
+            Orientation::Vertical => self.width(),
+        };
+        (0..length).map(|i| offset + i * step).collect()
+    }
+
+    /// Offsets of the cells adjacent to a number under the schematic's
+    /// neighborhood, in row-major order and excluding the number itself.
     pub fn neighbors(&self, offset: usize, length: usize) -> Vec<usize> {
-        let (x, y) = self.offset_to_coord(offset);
-        let (x, y) = (x as isize, y as isize);
-        let width = self.width as isize;
-        let height = self.height() as isize;
+        let run = self.run(offset, length);
+        let (width, height) = (self.width() as isize, self.height() as isize);
+        let offsets = self.neighborhood.shape.offsets();
         let mut result = Vec::new();
-        for cy in (y - 1)..=(y + 1) {
-            for cx in (x - 1)..(x + length as isize + 1) {
-                if cx < 0 || cy < 0 || cx >= width || cy >= height {
+        for &cell in &run {
+            let coord = self.offset_to_coord(cell);
+            for (dx, dy) in &offsets {
+                let (mut x, mut y) = (coord.x as isize + dx, coord.y as isize + dy);
+                if self.neighborhood.wrap {
+                    x = x.rem_euclid(width);
+                    y = y.rem_euclid(height);
+                } else if x < 0 || y < 0 || x >= width || y >= height {
                     continue;
                 }
-                if y == cy && x <= cx && cx < x + length as isize {
-                    continue;
+                let neighbor = self.coord_to_offset(grid::Point::new(x as usize, y as usize));
+                if !run.contains(&neighbor) {
+                    result.push(neighbor);
                 }
-                result.push(self.coord_to_offset((cx as usize, cy as usize)));
             }
         }
+        result.sort();
+        result.dedup();
         result
     }
 
-    pub fn offset_to_coord(&self, offset: usize) -> (usize, usize) {
-        (offset % self.width, offset / self.width)
+    pub fn offset_to_coord(&self, offset: usize) -> grid::Point {
+        grid::Point::new(offset % self.width(), offset / self.width())
+    }
+
+    pub fn coord_to_offset(&self, coord: grid::Point) -> usize {
+        coord.y * self.width() + coord.x
     }
 
-    pub fn coord_to_offset(&self, (x, y): (usize, usize)) -> usize {
-        y * self.width + x
+    /// The cell at a row-major offset into the schematic.
+    pub fn point(&self, offset: usize) -> Point {
+        self.grid.cells()[offset]
+    }
+
+    pub fn width(&self) -> usize {
+        self.grid.width()
     }
 
     pub fn height(&self) -> usize {
-        self.data.len() / self.width
+        self.grid.height()
     }
 
     pub fn numbers(&self) -> Vec<Number> {
+        let lines: Vec<Vec<usize>> = match self.orientation {
+            Orientation::Horizontal => (0..self.height())
+                .map(|y| (0..self.width()).map(|x| y * self.width() + x).collect())
+                .collect(),
+            Orientation::Vertical => (0..self.width())
+                .map(|x| (0..self.height()).map(|y| y * self.width() + x).collect())
+                .collect(),
+        };
         let mut result = Vec::new();
-        let mut iter = self.data.iter().enumerate();
-        while let Some((offset, point)) = iter.next() {
-            let (mut x, _) = self.offset_to_coord(offset);
-            let mut chars = Vec::new();
-            match point {
-                Point::Num(n) => chars.push(*n),
-                _ => continue,
-            }
-            x += 1;
-            while x < self.width {
-                match iter.next() {
-                    Some((_, Point::Num(n))) => chars.push(*n),
-                    _ => break,
+        for line in lines {
+            let mut iter = line.into_iter().peekable();
+            while let Some(offset) = iter.next() {
+                let mut chars = Vec::new();
+                match self.point(offset) {
+                    Point::Num(n) => chars.push(n),
+                    _ => continue,
+                }
+                while let Some(Point::Num(n)) = iter.peek().map(|next| self.point(*next)) {
+                    chars.push(n);
+                    iter.next();
                 }
-                x += 1;
+                result.push(Number { offset, chars });
             }
-            result.push(Number { offset, chars });
         }
+        result.sort_by_key(|n| n.offset);
         result
     }
 }
@@ -149,14 +251,15 @@ impl Schematic {
 mod tests {
     #[allow(unused_imports)]
     use super::*;
+    use neighborhood::Shape;
 
     const EXAMPLE: &str = include_str!("../example.txt");
 
     #[test]
     fn schematic_numbers() {
-        let schematic = Schematic::from("123\n456\n789");
+        let schematic = Schematic::try_from("123\n456\n789").unwrap();
         let numbers = schematic.numbers();
-        assert_eq!(schematic.width, 3);
+        assert_eq!(schematic.width(), 3);
         assert_eq!(schematic.height(), 3);
         //assert_eq!(numbers[0].value(), 123);
         assert_eq!(numbers[1].value(), 456);
@@ -166,7 +269,7 @@ mod tests {
 
     #[test]
     fn example_schematic_numbers() {
-        let schematic = Schematic::from(EXAMPLE);
+        let schematic = Schematic::try_from(EXAMPLE).unwrap();
         let raw_numbers = schematic.numbers();
         dbg!(&raw_numbers);
         let numbers: Vec<u32> = raw_numbers.into_iter().map(|n| n.value()).collect();
@@ -175,7 +278,7 @@ mod tests {
 
     #[test]
     fn example_neighbors() {
-        let schematic = Schematic::from(EXAMPLE);
+        let schematic = Schematic::try_from(EXAMPLE).unwrap();
         let neighbors = schematic.neighbors(0, 3);
         assert_eq!(neighbors, vec![3, 10, 11, 12, 13]);
         let neighbors = schematic.neighbors(22, 2);
@@ -186,4 +289,76 @@ mod tests {
             31, 32, 33, 34,
         ]);
     }
+
+    #[test]
+    fn parse_line_endings() {
+        let unix = Schematic::try_from("1.\n.#\n").unwrap();
+        let windows = Schematic::try_from("1.\r\n.#").unwrap();
+        let mac = Schematic::try_from("1.\r.#\r").unwrap();
+        for schematic in [&unix, &windows, &mac] {
+            assert_eq!(schematic.width(), 2);
+            assert_eq!(schematic.height(), 2);
+            assert_eq!(schematic.point(3), Point::Symbol('#'));
+        }
+    }
+
+    #[test]
+    fn parse_single_line() {
+        let schematic = Schematic::try_from("467..114").unwrap();
+        assert_eq!(schematic.width(), 8);
+        assert_eq!(schematic.height(), 1);
+        assert_eq!(schematic.offset_to_coord(5), grid::Point::new(5, 0));
+        assert!(Schematic::try_from("").is_err());
+        assert!(Schematic::try_from("\n\n").is_err());
+    }
+
+    #[test]
+    fn parse_ragged_rows() {
+        let err = Schematic::try_from("123\n45\n678").unwrap_err();
+        assert_eq!(err.to_string(), "Row 2, column 3: expected 3 columns like row 1 but found 2");
+        let err = Schematic::try_from("12\n345").unwrap_err();
+        assert!(err.to_string().starts_with("Row 2, column 3:"));
+        let err = Schematic::try_from("12\n3 5").unwrap_err();
+        assert!(err.to_string().starts_with("Row 2, column 2:"));
+        let err = Schematic::try_from("12\n\n34").unwrap_err();
+        assert_eq!(err.to_string(), "Row 2 is empty");
+
+        let padded = Schematic::parse("123\n45\n6", ParseOptions { pad: true }).unwrap();
+        assert_eq!(padded.width(), 3);
+        assert_eq!(padded.point(5), Point::Empty);
+        let values: Vec<u32> = padded.numbers().iter().map(Number::value).collect();
+        assert_eq!(values, vec![123, 45, 6]);
+    }
+
+    #[test]
+    fn shaped_neighbors() {
+        let schematic = Schematic::try_from(EXAMPLE).unwrap();
+        let neighborhood = |shape| Neighborhood { shape, wrap: false };
+        let von_neumann = schematic.clone().with_neighborhood(neighborhood(Shape::VonNeumann));
+        assert_eq!(von_neumann.neighbors(22, 2), vec![12, 13, 21, 24, 32, 33]);
+        let chebyshev = schematic.clone().with_neighborhood(neighborhood(Shape::Chebyshev(2)));
+        assert_eq!(chebyshev.neighbors(0, 1), vec![1, 2, 10, 11, 12, 20, 21, 22]);
+        let stencil = schematic.with_neighborhood(neighborhood("stencil:0,2;3,0".parse().unwrap()));
+        assert_eq!(stencil.neighbors(0, 3), vec![3, 4, 5, 20, 21, 22]);
+    }
+
+    #[test]
+    fn wrapped_neighbors() {
+        let schematic = Schematic::try_from("1..\n...\n..#")
+            .unwrap()
+            .with_neighborhood(Neighborhood { shape: Shape::Moore, wrap: true });
+        assert_eq!(schematic.neighbors(0, 1), vec![1, 2, 3, 4, 5, 6, 7, 8]);
+        assert_eq!(schematic.graph().parts().count(), 1);
+    }
+
+    #[test]
+    fn vertical_numbers() {
+        let schematic = Schematic::try_from("1.2\n3*.\n..4")
+            .unwrap()
+            .with_orientation(Orientation::Vertical);
+        let values: Vec<u32> = schematic.numbers().iter().map(Number::value).collect();
+        assert_eq!(values, vec![13, 2, 4]);
+        assert_eq!(schematic.run(0, 2), vec![0, 3]);
+        assert_eq!(schematic.neighbors(0, 2), vec![1, 4, 6, 7]);
+    }
 }
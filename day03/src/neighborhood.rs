@@ -0,0 +1,80 @@
+//! Which cells count as touching a number.
+use std::str::FromStr;
+
+/// The cells around a single cell that are considered adjacent to it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Shape {
+    /// The four orthogonal neighbors.
+    VonNeumann,
+    /// The eight surrounding cells, as in the original puzzle.
+    Moore,
+    /// Every cell within `r` steps horizontally and vertically.
+    Chebyshev(usize),
+    /// Arbitrary `(dx, dy)` offsets.
+    Stencil(Vec<(isize, isize)>),
+}
+
+impl Shape {
+    /// The `(dx, dy)` offsets of the shape, never including `(0, 0)`.
+    pub fn offsets(&self) -> Vec<(isize, isize)> {
+        let square = |r: isize| {
+            (-r..=r)
+                .flat_map(move |dy| (-r..=r).map(move |dx| (dx, dy)))
+                .filter(|d| *d != (0, 0))
+                .collect()
+        };
+        match self {
+            Shape::VonNeumann => vec![(0, -1), (-1, 0), (1, 0), (0, 1)],
+            Shape::Moore => square(1),
+            Shape::Chebyshev(r) => square(*r as isize),
+            Shape::Stencil(offsets) => offsets.iter().copied().filter(|d| *d != (0, 0)).collect(),
+        }
+    }
+}
+
+/// Parses `von-neumann`, `moore`, `chebyshev:R` or `stencil:dx,dy;dx,dy;...`.
+impl FromStr for Shape {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once(':') {
+            None if s == "von-neumann" => Ok(Shape::VonNeumann),
+            None if s == "moore" => Ok(Shape::Moore),
+            Some(("chebyshev", r)) => Ok(Shape::Chebyshev(r.parse()?)),
+            Some(("stencil", offsets)) => {
+                let mut stencil = Vec::new();
+                for offset in offsets.split(';') {
+                    let (dx, dy) = offset
+                        .split_once(',')
+                        .ok_or_else(|| anyhow::anyhow!("Invalid stencil offset {offset:?}"))?;
+                    stencil.push((dx.trim().parse()?, dy.trim().parse()?));
+                }
+                Ok(Shape::Stencil(stencil))
+            }
+            _ => Err(anyhow::anyhow!("Invalid neighborhood shape {s:?}")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Neighborhood {
+    pub shape: Shape,
+    /// Treat the schematic as a torus so neighbors past one edge come from the other.
+    pub wrap: bool,
+}
+
+impl Default for Neighborhood {
+    fn default() -> Self {
+        Neighborhood { shape: Shape::Moore, wrap: false }
+    }
+}
+
+/// The direction digits are read in to form a number.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Orientation {
+    /// Left to right along a row.
+    #[default]
+    Horizontal,
+    /// Top to bottom along a column.
+    Vertical,
+}
@@ -0,0 +1,259 @@
+//! Drawing a schematic with its parts, isolated numbers and gears told apart.
+use std::fmt::Write;
+use std::str::FromStr;
+
+use crate::graph::{GearRule, PartGraph};
+use crate::{Point, Schematic};
+
+// Every style starts with a reset so bold or dim never carries over to the
+// cells after a change of color.
+const RESET: &str = "\x1b[0m";
+const GREEN: &str = "\x1b[0;32m";
+const RED: &str = "\x1b[0;31m";
+const GEAR: &str = "\x1b[0;1;33m";
+const DIM: &str = "\x1b[0;2m";
+
+/// A rectangle of the schematic to draw, in cells. Parsed from `x,y,w,h`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Window {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl FromStr for Window {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts = s
+            .split(',')
+            .map(|part| part.trim().parse::<usize>())
+            .collect::<Result<Vec<_>, _>>()?;
+        match parts[..] {
+            [x, y, width, height] => Ok(Window { x, y, width, height }),
+            _ => Err(anyhow::anyhow!("Invalid window {s:?}, expected x,y,w,h")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Cell {
+    Empty,
+    Part(usize),
+    Isolated(usize),
+    Gear(usize),
+    Symbol(usize),
+}
+
+pub struct Renderer<'a> {
+    schematic: &'a Schematic,
+    graph: PartGraph,
+    cells: Vec<Cell>,
+    window: Window,
+}
+
+impl<'a> Renderer<'a> {
+    /// Classify every cell of `schematic`, treating symbols that match `rule` as gears.
+    pub fn new(schematic: &'a Schematic, rule: &GearRule) -> Self {
+        let graph = schematic.graph();
+        let mut cells = vec![Cell::Empty; schematic.grid.len()];
+        for (i, number) in graph.numbers().iter().enumerate() {
+            let cell = match graph.symbols_adjacent_to(i).next() {
+                Some(_) => Cell::Part(i),
+                None => Cell::Isolated(i),
+            };
+            for offset in schematic.run(number.offset, number.len()) {
+                cells[offset] = cell;
+            }
+        }
+        for (i, symbol) in graph.symbols().iter().enumerate() {
+            cells[symbol.offset] = Cell::Symbol(i);
+        }
+        for i in graph.gears(rule) {
+            cells[graph.symbols()[i].offset] = Cell::Gear(i);
+        }
+        let window = Window { x: 0, y: 0, width: schematic.width(), height: schematic.height() };
+        Renderer { schematic, graph, cells, window }
+    }
+
+    /// Only draw the part of the schematic inside `window`.
+    pub fn with_window(mut self, window: Window) -> Self {
+        self.window = window;
+        self
+    }
+
+    /// Row-major offsets of each visible row, clipped to the schematic.
+    fn rows(&self) -> impl Iterator<Item = std::ops::Range<usize>> + '_ {
+        let width = self.schematic.width();
+        // A window running past the schematic, even past `usize::MAX`, is clipped.
+        let x_end = self.window.x.saturating_add(self.window.width).min(width);
+        let y_end = self.window.y.saturating_add(self.window.height).min(self.schematic.height());
+        let x_start = self.window.x.min(x_end);
+        (self.window.y.min(y_end)..y_end).map(move |y| y * width + x_start..y * width + x_end)
+    }
+
+    fn char_at(&self, offset: usize) -> char {
+        match self.schematic.point(offset) {
+            Point::Empty => '.',
+            point => char::from(point),
+        }
+    }
+
+    /// Text for every cell with ANSI escapes around parts, isolated numbers, gears
+    /// and dimmed other symbols.
+    pub fn ansi(&self) -> String {
+        let mut out = String::new();
+        for row in self.rows() {
+            let mut current = None;
+            for offset in row {
+                let color = match self.cells[offset] {
+                    Cell::Empty => None,
+                    Cell::Part(_) => Some(GREEN),
+                    Cell::Isolated(_) => Some(RED),
+                    Cell::Gear(_) => Some(GEAR),
+                    Cell::Symbol(_) => Some(DIM),
+                };
+                if color != current {
+                    out.push_str(color.unwrap_or(RESET));
+                    current = color;
+                }
+                out.push(self.char_at(offset));
+            }
+            if current.is_some() {
+                out.push_str(RESET);
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    fn tooltip(&self, cell: Cell) -> Option<String> {
+        let describe = |offset: usize| {
+            let coord = self.schematic.offset_to_coord(offset);
+            format!("({},{})", coord.x, coord.y)
+        };
+        match cell {
+            Cell::Empty => None,
+            Cell::Part(n) | Cell::Isolated(n) => {
+                let symbols: Vec<String> = self
+                    .graph
+                    .symbols_adjacent_to(n)
+                    .map(|s| format!("{} at {}", s.char, describe(s.offset)))
+                    .collect();
+                let value = self.graph.numbers()[n].value();
+                Some(match symbols.is_empty() {
+                    true => format!("{value}: no adjacent symbols"),
+                    false => format!("{value}: adjacent to {}", symbols.join(", ")),
+                })
+            }
+            Cell::Gear(s) | Cell::Symbol(s) => {
+                let values: Vec<String> = self
+                    .graph
+                    .numbers_adjacent_to(s)
+                    .map(|n| n.value().to_string())
+                    .collect();
+                let at = describe(self.graph.symbols()[s].offset);
+                Some(format!("{at}: adjacent to [{}]", values.join(", ")))
+            }
+        }
+    }
+
+    /// A standalone HTML page with one span per number or symbol, each with a
+    /// tooltip listing what it is adjacent to.
+    pub fn html(&self) -> String {
+        let mut out = String::from(concat!(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<style>\n",
+            "pre { font-family: monospace; }\n",
+            ".part { color: green; }\n",
+            ".isolated { color: red; }\n",
+            ".gear { color: darkorange; font-weight: bold; }\n",
+            ".symbol { color: gray; }\n",
+            "</style>\n</head>\n<body>\n<pre>\n",
+        ));
+        for row in self.rows() {
+            let mut offsets = row.peekable();
+            while let Some(offset) = offsets.next() {
+                let cell = self.cells[offset];
+                let mut text = String::new();
+                push_escaped(&mut text, self.char_at(offset));
+                // Keep the digits of a number together in a single span.
+                while let Some(next) = offsets.peek() {
+                    if cell == Cell::Empty || self.cells[*next] != cell {
+                        break;
+                    }
+                    push_escaped(&mut text, self.char_at(*next));
+                    offsets.next();
+                }
+                let class = match cell {
+                    Cell::Empty => {
+                        out.push_str(&text);
+                        continue;
+                    }
+                    Cell::Part(_) => "part",
+                    Cell::Isolated(_) => "isolated",
+                    Cell::Gear(_) => "gear",
+                    Cell::Symbol(_) => "symbol",
+                };
+                let mut title = String::new();
+                for c in self.tooltip(cell).unwrap_or_default().chars() {
+                    push_escaped(&mut title, c);
+                }
+                write!(out, "<span class=\"{class}\" title=\"{title}\">{text}</span>").unwrap();
+            }
+            out.push('\n');
+        }
+        out.push_str("</pre>\n</body>\n</html>\n");
+        out
+    }
+}
+
+fn push_escaped(out: &mut String, c: char) {
+    match c {
+        '&' => out.push_str("&amp;"),
+        '<' => out.push_str("&lt;"),
+        '>' => out.push_str("&gt;"),
+        '"' => out.push_str("&quot;"),
+        _ => out.push(c),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ansi() {
+        let schematic = Schematic::try_from("12*3\n..#.\n7...").unwrap();
+        let rendered = Renderer::new(&schematic, &GearRule::STANDARD).ansi();
+        assert_eq!(
+            rendered,
+            "\x1b[0;32m12\x1b[0;1;33m*\x1b[0;32m3\x1b[0m\n\
+             ..\x1b[0;2m#\x1b[0m.\n\
+             \x1b[0;31m7\x1b[0m...\n",
+        );
+    }
+
+    #[test]
+    fn test_window() {
+        let schematic = Schematic::try_from("12*3\n..#.\n7...").unwrap();
+        let window: Window = "1,1,10,2".parse().unwrap();
+        let rendered = Renderer::new(&schematic, &GearRule::STANDARD)
+            .with_window(window)
+            .ansi();
+        assert_eq!(rendered, format!(".{DIM}#{RESET}.\n...\n"));
+        assert!("1,2,3".parse::<Window>().is_err());
+        let huge = Window { x: 1, y: 2, width: usize::MAX, height: usize::MAX };
+        let rendered = Renderer::new(&schematic, &GearRule::STANDARD).with_window(huge).ansi();
+        assert_eq!(rendered, "...\n");
+    }
+
+    #[test]
+    fn test_html() {
+        let schematic = Schematic::try_from("12&3\n7...").unwrap();
+        let html = Renderer::new(&schematic, &GearRule::STANDARD).html();
+        assert!(html.contains("<span class=\"part\" title=\"12: adjacent to &amp; at (2,0)\">12</span>"));
+        assert!(html.contains("<span class=\"symbol\" title=\"(2,0): adjacent to [12, 3]\">&amp;</span>"));
+        assert!(html.contains("<span class=\"isolated\" title=\"7: no adjacent symbols\">7</span>..."));
+    }
+}
@@ -0,0 +1,84 @@
+//! Counting matches over a generated deck, as a `HashSet` lookup per number
+//! against the bitset intersection used by `Card::matches`.
+use std::collections::HashSet;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use day04::NumberSet;
+
+const CARDS: usize = 2_000_000;
+const WINNING: usize = 10;
+const NUMBERS: usize = 25;
+
+/// Winning numbers and hand for each card, drawn from 1..100 like the puzzle
+/// input with a fixed xorshift generator. Neither list repeats a number, as on
+/// the real cards, so both ways of counting agree.
+fn deck() -> Vec<(Vec<u32>, Vec<u32>)> {
+    let mut state = 0x2545_f491_4f6c_dd1d_u64;
+    let mut next = move || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        (state % 99) as u32 + 1
+    };
+    let mut distinct = move |count: usize| {
+        let mut drawn: Vec<u32> = Vec::with_capacity(count);
+        while drawn.len() < count {
+            let n = next();
+            if !drawn.contains(&n) {
+                drawn.push(n);
+            }
+        }
+        drawn
+    };
+    (0..CARDS).map(|_| (distinct(WINNING), distinct(NUMBERS))).collect()
+}
+
+fn matches(c: &mut Criterion) {
+    let deck = deck();
+    let hash_sets: Vec<(HashSet<u32>, &[u32])> = deck
+        .iter()
+        .map(|(winning, numbers)| (winning.iter().copied().collect(), numbers.as_slice()))
+        .collect();
+    let bit_sets: Vec<(NumberSet, NumberSet)> = deck
+        .iter()
+        .map(|(winning, numbers)| {
+            (winning.iter().copied().collect(), numbers.iter().copied().collect())
+        })
+        .collect();
+
+    let hash_set_count = |(winning, numbers): &(HashSet<u32>, &[u32])| {
+        numbers.iter().filter(|n| winning.contains(n)).count()
+    };
+    let bit_set_count = |(winning, hand): &(NumberSet, NumberSet)| winning.intersection_count(hand);
+    assert_eq!(
+        hash_sets.iter().map(hash_set_count).sum::<usize>(),
+        bit_sets.iter().map(bit_set_count).sum::<usize>(),
+    );
+
+    let mut group = c.benchmark_group("matches");
+    group.sample_size(10);
+    group.bench_function("hash_set", |b| {
+        b.iter(|| hash_sets.iter().map(hash_set_count).sum::<usize>())
+    });
+    group.bench_function("bit_set", |b| {
+        b.iter(|| bit_sets.iter().map(bit_set_count).sum::<usize>())
+    });
+    group.bench_function("build_hash_set", |b| {
+        b.iter(|| {
+            deck.iter()
+                .map(|(winning, _)| winning.iter().copied().collect::<HashSet<u32>>())
+                .collect::<Vec<_>>()
+        })
+    });
+    group.bench_function("build_bit_set", |b| {
+        b.iter(|| {
+            deck.iter()
+                .map(|(winning, _)| winning.iter().copied().collect::<NumberSet>())
+                .collect::<Vec<_>>()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, matches);
+criterion_main!(benches);
@@ -0,0 +1,64 @@
+//! Syntax tree for a single card line, derived from `card.pest`.
+//!
+//! Numbers are kept as spans so that duplicates and out of range values can be
+//! reported against the text they came from.
+// The derive clones spans, which are `Copy` since pest 2.5.
+#![allow(clippy::clone_on_copy)]
+use from_pest::FromPest;
+use pest::{Parser, Span};
+use pest_ast::FromPest;
+
+use crate::{CardParser, Rule};
+
+#[derive(Debug, FromPest)]
+#[pest_ast(rule(Rule::card))]
+pub struct Card<'a> {
+    pub id: Id<'a>,
+    pub winning: Vec<Winning<'a>>,
+    pub numbers: Vec<Number<'a>>,
+    _eoi: Eoi,
+}
+
+#[derive(Debug, FromPest)]
+#[pest_ast(rule(Rule::id))]
+pub struct Id<'a> {
+    #[pest_ast(outer())]
+    pub span: Span<'a>,
+}
+
+#[derive(Debug, FromPest)]
+#[pest_ast(rule(Rule::winning))]
+pub struct Winning<'a> {
+    #[pest_ast(outer())]
+    pub span: Span<'a>,
+}
+
+#[derive(Debug, FromPest)]
+#[pest_ast(rule(Rule::number))]
+pub struct Number<'a> {
+    #[pest_ast(outer())]
+    pub span: Span<'a>,
+}
+
+/// The end of the line, so that nothing may follow the last number.
+#[derive(Debug, FromPest)]
+#[pest_ast(rule(Rule::EOI))]
+struct Eoi;
+
+impl<'a> Card<'a> {
+    pub fn parse(line: &'a str) -> anyhow::Result<Self> {
+        let mut pairs = CardParser::parse(Rule::card, line)?;
+        Card::from_pest(&mut pairs).map_err(|err| anyhow::anyhow!("{err:?}"))
+    }
+}
+
+/// Parse the digits of `span` as a `T`, naming `what` if it does not fit.
+pub(crate) fn parse_span<T: std::str::FromStr>(span: &Span, what: &str) -> anyhow::Result<T> {
+    span.as_str().parse().map_err(|_| {
+        anyhow::anyhow!(
+            "{what} {} at column {} is out of range",
+            span.as_str(),
+            span.start() + 1,
+        )
+    })
+}
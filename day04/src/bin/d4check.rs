@@ -0,0 +1,18 @@
+use day04::{validate, Severity};
+
+fn main() -> anyhow::Result<()> {
+    let filename = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| "input.txt".to_string());
+    let input = std::fs::read_to_string(&filename)?;
+    let (cards, issues) = validate(&input);
+    for issue in &issues {
+        eprintln!("{filename}: {issue}");
+    }
+    let errors = issues.iter().filter(|i| i.severity == Severity::Error).count();
+    if errors > 0 {
+        anyhow::bail!("{errors} errors in {filename}");
+    }
+    println!("{} cards, {} warnings", cards.len(), issues.len());
+    Ok(())
+}
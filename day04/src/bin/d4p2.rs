@@ -1,22 +1,8 @@
 use std::fs::File;
 use std::io::prelude::*;
 use std::io::BufReader;
-use std::collections::HashMap;
 
-use day04::Card;
-
-fn play(cards: &[Card]) -> HashMap<usize, u32> {
-    let mut copies = HashMap::new();
-    for (card, id) in cards.iter().zip(1..) {
-        let current_copies = *copies.get(&id).unwrap_or(&1);
-        let won = card.winning_numbers().count();
-        for won_id in id + 1..id + 1 + won {
-            let won_copies = copies.entry(won_id).or_insert(1);
-            *won_copies += current_copies;
-        }
-    }
-    copies
-}
+use day04::{Card, CopyEngine};
 
 fn main() -> anyhow::Result<()> {
     let filename = std::env::args()
@@ -24,17 +10,13 @@ fn main() -> anyhow::Result<()> {
         .unwrap_or_else(|| "input.txt".to_string());
     let f = File::open(filename)?;
     let reader = BufReader::new(f);
-    let mut cards = Vec::new();
+    let mut engine = CopyEngine::default();
     for line in reader.lines() {
         let card = Card::try_from(line?)?;
-        cards.push(card);
+        engine.push(card.matches())?;
     }
-    let copies = play(&cards);
-    dbg!(&copies);
-    let total = (1..=cards.len())
-        .map(|id| copies.get(&id).unwrap_or(&1))
-        .sum::<u32>();
-    println!("Solution: {}", total);
+    eprintln!("{} cards, at most {} with copies pending", engine.cards(), engine.peak_window());
+    println!("Solution: {}", engine.total());
     Ok(())
 }
 
@@ -46,19 +28,12 @@ mod tests {
 
     #[test]
     fn test_play() {
-        let cards: Vec<Card> = EXAMPLE.lines()
+        let mut engine = CopyEngine::default();
+        let copies: Vec<u64> = EXAMPLE.lines()
             .map(|line| Card::try_from(line.to_string()).unwrap())
+            .map(|card| engine.push(card.matches()).unwrap())
             .collect();
-        let copies = play(&cards);
-        assert_eq!(copies.get(&1), None);
-        assert_eq!(copies.get(&2), Some(&2));
-        assert_eq!(copies.get(&3), Some(&4));
-        assert_eq!(copies.get(&4), Some(&8));
-        assert_eq!(copies.get(&5), Some(&14));
-        assert_eq!(copies.get(&6), None);
-        let sum = (1..=cards.len())
-            .map(|id| copies.get(&id).unwrap_or(&1))
-            .sum::<u32>();
-        assert_eq!(sum, 30);
+        assert_eq!(copies, vec![1, 2, 4, 8, 14, 1]);
+        assert_eq!(engine.total(), 30);
     }
 }
@@ -0,0 +1,49 @@
+use std::str::FromStr;
+use clap::Parser;
+
+use day04::stats::Report;
+use day04::validate;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+enum Format {
+    #[default] Table,
+    Json,
+}
+
+impl FromStr for Format {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "table" => Ok(Format::Table),
+            "json" => Ok(Format::Json),
+            _ => Err(anyhow::anyhow!("Invalid format")),
+        }
+    }
+}
+
+#[derive(Debug, Parser)]
+struct Args {
+    #[arg(default_value = "input.txt")]
+    filename: String,
+    #[arg(long)]
+    format: Option<Format>,
+    /// How many of the cards contributing most to the part 2 total to list
+    #[arg(long, default_value_t = 10)]
+    top: usize,
+}
+
+fn main() -> anyhow::Result<()> {
+    let args = Args::parse();
+    let input = std::fs::read_to_string(&args.filename)?;
+    let (cards, issues) = validate(&input);
+    for issue in &issues {
+        eprintln!("{}: {issue}", args.filename);
+    }
+    let report = Report::new(&cards)?.truncate(args.top);
+    match args.format.unwrap_or_default() {
+        Format::Table => println!("{report}"),
+        Format::Json => println!("{}", serde_json::to_string_pretty(&report)?),
+    }
+    Ok(())
+}
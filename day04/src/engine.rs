@@ -0,0 +1,150 @@
+//! Counting scratchcard copies without holding the whole deck in memory.
+//!
+//! A card can only win copies of the cards right after it, so the only state
+//! needed is how many extra copies each of the next few cards has already won.
+//! That is kept in a ring buffer as long as the largest number of wins seen.
+use anyhow::Context;
+
+use crate::Card;
+
+#[derive(Debug, Clone, Default)]
+pub struct CopyEngine {
+    /// Extra copies won by upcoming cards, starting at `head`.
+    pending: Vec<u64>,
+    head: usize,
+    /// How many upcoming cards currently have copies pending.
+    window: usize,
+    peak_window: usize,
+    cards: usize,
+    total: u64,
+}
+
+impl CopyEngine {
+    /// An engine whose ring buffer starts with room for cards winning up to
+    /// `max_wins` numbers. It grows if a card wins more.
+    pub fn new(max_wins: usize) -> Self {
+        CopyEngine { pending: vec![0; max_wins], ..Default::default() }
+    }
+
+    /// Process the next card, which matched `wins` numbers, and return how many
+    /// copies of it were held including the original.
+    pub fn push(&mut self, wins: usize) -> anyhow::Result<u64> {
+        let mut copies = 1;
+        if !self.pending.is_empty() {
+            copies += std::mem::take(&mut self.pending[self.head]);
+            self.head = (self.head + 1) % self.pending.len();
+        }
+        self.window = self.window.saturating_sub(1);
+        self.cards += 1;
+        self.total = self
+            .total
+            .checked_add(copies)
+            .with_context(|| format!("Total card count overflowed at card {}", self.cards))?;
+        if wins > self.pending.len() {
+            self.grow(wins);
+        }
+        let len = self.pending.len();
+        for i in 0..wins {
+            let slot = &mut self.pending[(self.head + i) % len];
+            *slot = slot
+                .checked_add(copies)
+                .with_context(|| format!("Copy count overflowed for card {}", self.cards + i + 1))?;
+        }
+        self.window = self.window.max(wins);
+        self.peak_window = self.peak_window.max(self.window);
+        Ok(copies)
+    }
+
+    /// Resize the ring to `len` slots, moving `head` back to the start.
+    fn grow(&mut self, len: usize) {
+        self.pending.rotate_left(self.head);
+        self.pending.resize(len, 0);
+        self.head = 0;
+    }
+
+    /// Cards processed so far, not counting copies.
+    pub fn cards(&self) -> usize {
+        self.cards
+    }
+
+    /// Copies of every card processed so far, originals included.
+    pub fn total(&self) -> u64 {
+        self.total
+    }
+
+    /// The largest number of upcoming cards that had copies pending at once.
+    pub fn peak_window(&self) -> usize {
+        self.peak_window
+    }
+
+    /// Run the engine over `cards`, yielding each card's id and copy count as
+    /// it is reached.
+    pub fn copies<I>(self, cards: I) -> Copies<I::IntoIter>
+    where
+        I: IntoIterator<Item = Card>,
+    {
+        Copies { cards: cards.into_iter(), engine: self }
+    }
+}
+
+/// Iterator returned by [`CopyEngine::copies`].
+pub struct Copies<I> {
+    cards: I,
+    engine: CopyEngine,
+}
+
+impl<I> Copies<I> {
+    pub fn engine(&self) -> &CopyEngine {
+        &self.engine
+    }
+}
+
+impl<I: Iterator<Item = Card>> Iterator for Copies<I> {
+    type Item = anyhow::Result<(usize, u64)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let card = self.cards.next()?;
+        Some(self.engine.push(card.matches()).map(|n| (card.id, n)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = include_str!("../example.txt");
+
+    fn cards() -> impl Iterator<Item = Card> {
+        EXAMPLE.lines().map(|line| Card::try_from(line.to_string()).unwrap())
+    }
+
+    #[test]
+    fn test_example() {
+        let mut copies = CopyEngine::new(0).copies(cards());
+        let counts: Vec<(usize, u64)> = copies.by_ref().map(Result::unwrap).collect();
+        assert_eq!(counts, vec![(1, 1), (2, 2), (3, 4), (4, 8), (5, 14), (6, 1)]);
+        assert_eq!(copies.engine().total(), 30);
+        assert_eq!(copies.engine().cards(), 6);
+        assert_eq!(copies.engine().peak_window(), 4);
+    }
+
+    #[test]
+    fn test_window_is_independent_of_initial_size() {
+        let wins = [3, 0, 5, 1, 1, 0, 2, 7, 0, 0, 0, 0, 0, 0, 0];
+        let run = |size| {
+            let mut engine = CopyEngine::new(size);
+            let copies: Vec<u64> = wins.iter().map(|w| engine.push(*w).unwrap()).collect();
+            (copies, engine.total(), engine.peak_window())
+        };
+        assert_eq!(run(0), run(7));
+        assert_eq!(run(2), run(7));
+    }
+
+    #[test]
+    fn test_overflow() {
+        let mut engine = CopyEngine::new(2);
+        // Copies grow like the Fibonacci numbers when every card wins two.
+        let result: anyhow::Result<Vec<u64>> = (0..200).map(|_| engine.push(2)).collect();
+        assert!(result.is_err());
+    }
+}
@@ -1,21 +1,32 @@
-use std::collections::HashSet;
-
-use pest::Parser;
 use pest_derive::Parser;
 
+pub mod ast;
+pub mod engine;
+pub mod numbers;
+pub mod stats;
+pub mod validate;
+
+pub use engine::CopyEngine;
+pub use numbers::NumberSet;
+pub use validate::{validate, Issue, Severity};
+
 #[derive(Parser)]
 #[grammar = "src/card.pest"]
 pub struct CardParser;
 
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Card {
     pub id: usize,
-    pub winning: HashSet<u32>,
+    pub winning: NumberSet,
+    /// The numbers in the order they appear on the card.
     pub numbers: Vec<u32>,
+    /// The same numbers as `numbers`, as a set.
+    pub hand: NumberSet,
 }
 
 impl Card {
     pub fn points(&self) -> u32 {
-        let count = self.winning_numbers().count();
+        let count = self.matches();
         if count == 0 {
             0
         } else {
@@ -23,40 +34,59 @@ impl Card {
         }
     }
 
+    /// How many numbers in the hand are winning numbers, counting a number
+    /// each time it appears. Hands without repeats take the set intersection.
+    pub fn matches(&self) -> usize {
+        if self.hand.len() == self.numbers.len() {
+            self.winning.intersection_count(&self.hand)
+        } else {
+            self.winning_numbers().count()
+        }
+    }
+
     pub fn winning_numbers(&self) -> impl Iterator<Item = &u32> {
         self.numbers
             .iter()
-            .filter(|n| self.winning.contains(n))
+            .filter(|n| self.winning.contains(**n))
+    }
+}
+
+
+impl TryFrom<&ast::Card<'_>> for Card {
+    type Error = anyhow::Error;
+
+    fn try_from(card: &ast::Card) -> Result<Self, Self::Error> {
+        let numbers: Vec<u32> = card
+            .numbers
+            .iter()
+            .map(|n| ast::parse_span(&n.span, "Number"))
+            .collect::<anyhow::Result<_>>()?;
+        Ok(Card {
+            id: ast::parse_span(&card.id.span, "Card id")?,
+            winning: card
+                .winning
+                .iter()
+                .map(|n| ast::parse_span(&n.span, "Winning number"))
+                .collect::<anyhow::Result<_>>()?,
+            hand: numbers.iter().copied().collect(),
+            numbers,
+        })
     }
 }
 
+impl TryFrom<&str> for Card {
+    type Error = anyhow::Error;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        Card::try_from(&ast::Card::parse(value)?)
+    }
+}
 
 impl TryFrom<String> for Card {
     type Error = anyhow::Error;
 
     fn try_from(value: String) -> Result<Self, Self::Error> {
-        let pairs = CardParser::parse(Rule::card, &value)?;
-        let mut id = 0;
-        let mut winning = HashSet::new();
-        let mut numbers = Vec::new();
-        for field in pairs.flatten() {
-            match field.as_rule() {
-                Rule::card => continue,
-                Rule::id => {
-                    id = field.as_span().as_str().parse::<usize>()?;
-                },
-                Rule::winning => {
-                    let num = field.as_span().as_str().parse::<u32>()?;
-                    winning.insert(num);
-                },
-                Rule::number => {
-                    let num = field.as_span().as_str().parse::<u32>()?;
-                    numbers.push(num);
-                },
-                _ => panic!("unexpected rule: {:?}", field.as_rule()),
-            }
-        }
-        Ok(Card { id, winning, numbers })
+        Card::try_from(value.as_str())
     }
 }
 
@@ -76,6 +106,11 @@ mod tests {
         assert_eq!(cards.len(), 6);
         assert_eq!(cards[0].id, 1);
         assert_eq!(cards[0].winning, vec![41, 48, 83, 86, 17].into_iter().collect());
+        assert!(Card::try_from("Card 1: 4294967296 | 1").is_err());
+        assert!(Card::try_from("Card 1: 1 2 3").is_err());
+        // Trailing text is not silently dropped.
+        assert!(Card::try_from("Card 1: 1 2 | 1 x").is_err());
+        assert!(Card::try_from("Card 1: 1 2 | 1 2 |").is_err());
     }
 
     #[test]
@@ -90,4 +125,14 @@ mod tests {
         assert_eq!(cards[4].points(), 0);
         assert_eq!(cards[5].points(), 0);
     }
+
+    #[test]
+    fn test_repeated_numbers() {
+        // Every copy of a winning number in the hand is a match.
+        let card = Card::try_from("Card 1: 1 2 3 | 1 1 4 2").unwrap();
+        assert_eq!(card.matches(), 3);
+        assert_eq!(card.points(), 4);
+        let card = Card::try_from("Card 1: 1 1 | 1 5").unwrap();
+        assert_eq!(card.matches(), 1);
+    }
 }
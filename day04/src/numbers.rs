@@ -0,0 +1,153 @@
+//! Small sets of card numbers.
+//!
+//! Numbers on real cards are below 100, so a set usually fits in the bits of a
+//! single `u128` and intersecting two sets is an AND and a popcount. Sets with
+//! larger values fall back to a sorted vector.
+use std::fmt;
+
+/// Values below this are stored as bits.
+pub const BITS: u32 = u128::BITS;
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub enum NumberSet {
+    Bits(u128),
+    Sorted(Vec<u32>),
+}
+
+impl Default for NumberSet {
+    fn default() -> Self {
+        NumberSet::Bits(0)
+    }
+}
+
+impl NumberSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, n: u32) -> bool {
+        match self {
+            NumberSet::Bits(bits) if n < BITS => {
+                let added = *bits & (1 << n) == 0;
+                *bits |= 1 << n;
+                added
+            }
+            NumberSet::Bits(_) => {
+                let mut sorted: Vec<u32> = self.iter().collect();
+                sorted.push(n);
+                *self = NumberSet::Sorted(sorted);
+                true
+            }
+            NumberSet::Sorted(sorted) => match sorted.binary_search(&n) {
+                Ok(_) => false,
+                Err(i) => {
+                    sorted.insert(i, n);
+                    true
+                }
+            },
+        }
+    }
+
+    pub fn contains(&self, n: u32) -> bool {
+        match self {
+            NumberSet::Bits(bits) => n < BITS && bits & (1 << n) != 0,
+            NumberSet::Sorted(sorted) => sorted.binary_search(&n).is_ok(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        match self {
+            NumberSet::Bits(bits) => bits.count_ones() as usize,
+            NumberSet::Sorted(sorted) => sorted.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The numbers in ascending order.
+    pub fn iter(&self) -> Box<dyn Iterator<Item = u32> + '_> {
+        match self {
+            NumberSet::Bits(bits) => Box::new((0..BITS).filter(move |n| bits & (1 << n) != 0)),
+            NumberSet::Sorted(sorted) => Box::new(sorted.iter().copied()),
+        }
+    }
+
+    /// How many numbers are in both sets.
+    pub fn intersection_count(&self, other: &NumberSet) -> usize {
+        match (self, other) {
+            (NumberSet::Bits(a), NumberSet::Bits(b)) => (a & b).count_ones() as usize,
+            (NumberSet::Sorted(a), NumberSet::Sorted(b)) => {
+                let (mut i, mut j, mut count) = (0, 0, 0);
+                while i < a.len() && j < b.len() {
+                    match a[i].cmp(&b[j]) {
+                        std::cmp::Ordering::Less => i += 1,
+                        std::cmp::Ordering::Greater => j += 1,
+                        std::cmp::Ordering::Equal => {
+                            count += 1;
+                            i += 1;
+                            j += 1;
+                        }
+                    }
+                }
+                count
+            }
+            (bits @ NumberSet::Bits(_), sorted) | (sorted, bits @ NumberSet::Bits(_)) => {
+                bits.iter().filter(|n| sorted.contains(*n)).count()
+            }
+        }
+    }
+}
+
+impl FromIterator<u32> for NumberSet {
+    fn from_iter<I: IntoIterator<Item = u32>>(iter: I) -> Self {
+        let mut set = NumberSet::new();
+        for n in iter {
+            set.insert(n);
+        }
+        set
+    }
+}
+
+impl fmt::Debug for NumberSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bits() {
+        let set: NumberSet = [41, 48, 83, 86, 17, 48].into_iter().collect();
+        assert!(matches!(set, NumberSet::Bits(_)));
+        assert_eq!(set.len(), 5);
+        assert!(set.contains(83));
+        assert!(!set.contains(84));
+        assert!(!set.contains(1000));
+        assert_eq!(set.iter().collect::<Vec<_>>(), vec![17, 41, 48, 83, 86]);
+    }
+
+    #[test]
+    fn test_fallback() {
+        let mut set: NumberSet = [3, 127].into_iter().collect();
+        assert!(set.insert(128));
+        assert!(!set.insert(3));
+        assert_eq!(set, NumberSet::Sorted(vec![3, 127, 128]));
+    }
+
+    #[test]
+    fn test_intersection_count() {
+        let small: NumberSet = [83, 86, 6, 31, 17, 9, 48, 53].into_iter().collect();
+        let winning: NumberSet = [41, 48, 83, 86, 17].into_iter().collect();
+        let large: NumberSet = [41, 48, 83, 86, 17, 500].into_iter().collect();
+        let other: NumberSet = [48, 500, 1000].into_iter().collect();
+        assert_eq!(small.intersection_count(&winning), 4);
+        assert_eq!(small.intersection_count(&large), 4);
+        assert_eq!(large.intersection_count(&small), 4);
+        assert_eq!(large.intersection_count(&other), 2);
+    }
+}
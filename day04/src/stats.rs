@@ -0,0 +1,161 @@
+//! Summary statistics over a whole deck of scratchcards.
+//!
+//! Every card held in part 2 is either an original or a copy won by exactly one
+//! other card, so the cards form a forest rooted at the originals. Attribution
+//! credits each original with the size of its tree, and the longest chain is
+//! the deepest path through any tree.
+use std::fmt;
+
+use anyhow::Context;
+use serde::Serialize;
+
+use crate::Card;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Bucket {
+    pub matches: usize,
+    pub cards: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Attribution {
+    pub id: usize,
+    /// Copies of this card held at the end, including the original.
+    pub copies: u64,
+    /// Cards held because of the original of this card: itself and every copy
+    /// won by it, by the copies it won, and so on.
+    pub cascade: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Report {
+    pub cards: usize,
+    /// How many cards have each number of matches, by ascending matches.
+    pub histogram: Vec<Bucket>,
+    /// Mean part 1 score of a card.
+    pub expected_points: f64,
+    /// The part 2 answer.
+    pub total_copies: u64,
+    /// Originals by descending cascade size.
+    pub attribution: Vec<Attribution>,
+    /// Ids of the longest chain of cards each won by the one before.
+    pub longest_chain: Vec<usize>,
+}
+
+impl Report {
+    pub fn new(cards: &[Card]) -> anyhow::Result<Self> {
+        let matches: Vec<usize> = cards.iter().map(Card::matches).collect();
+        let mut histogram: Vec<Bucket> = Vec::new();
+        for m in 0..=matches.iter().copied().max().unwrap_or(0) {
+            let count = matches.iter().filter(|n| **n == m).count();
+            if count > 0 {
+                histogram.push(Bucket { matches: m, cards: count });
+            }
+        }
+        let points: u64 = cards.iter().map(|c| c.points() as u64).sum();
+        let expected_points = match cards.len() {
+            0 => 0.0,
+            n => points as f64 / n as f64,
+        };
+
+        // Cards won by card `i` are the next `matches[i]` ones, cut off at the end.
+        let won = |i: usize| i + 1..(i + 1 + matches[i]).min(cards.len());
+
+        // Walking backwards, every card a card wins already has its tree size
+        // and depth.
+        let mut tree = vec![0u64; cards.len()];
+        let mut depth = vec![0usize; cards.len()];
+        for i in (0..cards.len()).rev() {
+            tree[i] = won(i)
+                .try_fold(1u64, |acc, j| acc.checked_add(tree[j]))
+                .with_context(|| format!("Cascade of card {} overflowed", cards[i].id))?;
+            depth[i] = 1 + won(i).map(|j| depth[j]).max().unwrap_or(0);
+        }
+
+        let mut copies = vec![1u64; cards.len()];
+        for i in 0..cards.len() {
+            for j in won(i) {
+                copies[j] = copies[j]
+                    .checked_add(copies[i])
+                    .with_context(|| format!("Copies of card {} overflowed", cards[j].id))?;
+            }
+        }
+        let total_copies = copies
+            .iter()
+            .try_fold(0u64, |acc, c| acc.checked_add(*c))
+            .context("Total card count overflowed")?;
+
+        let mut attribution: Vec<Attribution> = cards
+            .iter()
+            .enumerate()
+            .map(|(i, card)| Attribution { id: card.id, copies: copies[i], cascade: tree[i] })
+            .collect();
+        attribution.sort_by(|a, b| b.cascade.cmp(&a.cascade).then(a.id.cmp(&b.id)));
+
+        let mut longest_chain = Vec::new();
+        let mut next = (0..cards.len()).max_by_key(|i| (depth[*i], std::cmp::Reverse(*i)));
+        while let Some(i) = next {
+            longest_chain.push(cards[i].id);
+            next = won(i).max_by_key(|j| (depth[*j], std::cmp::Reverse(*j)));
+        }
+
+        Ok(Report {
+            cards: cards.len(),
+            histogram,
+            expected_points,
+            total_copies,
+            attribution,
+            longest_chain,
+        })
+    }
+
+    /// Keep only the `n` originals with the largest cascades.
+    pub fn truncate(mut self, n: usize) -> Self {
+        self.attribution.truncate(n);
+        self
+    }
+}
+
+/// Plain text tables for the terminal.
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} cards, {:.3} expected points per card", self.cards, self.expected_points)?;
+        writeln!(f)?;
+        writeln!(f, "{:>7} {:>7}", "matches", "cards")?;
+        for bucket in &self.histogram {
+            writeln!(f, "{:>7} {:>7}", bucket.matches, bucket.cards)?;
+        }
+        writeln!(f)?;
+        writeln!(f, "{} cards in total after copying", self.total_copies)?;
+        writeln!(f, "{:>5} {:>12} {:>12} {:>7}", "card", "copies", "cascade", "share")?;
+        for a in &self.attribution {
+            let share = 100.0 * a.cascade as f64 / self.total_copies as f64;
+            writeln!(f, "{:>5} {:>12} {:>12} {:>6.2}%", a.id, a.copies, a.cascade, share)?;
+        }
+        writeln!(f)?;
+        let chain: Vec<String> = self.longest_chain.iter().map(|id| id.to_string()).collect();
+        write!(f, "longest chain ({} cards): {}", chain.len(), chain.join(" -> "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = include_str!("../example.txt");
+
+    #[test]
+    fn test_report() {
+        let cards: Vec<Card> = EXAMPLE.lines().map(|line| Card::try_from(line).unwrap()).collect();
+        let report = Report::new(&cards).unwrap();
+        let histogram: Vec<(usize, usize)> =
+            report.histogram.iter().map(|b| (b.matches, b.cards)).collect();
+        assert_eq!(histogram, vec![(0, 2), (1, 1), (2, 2), (4, 1)]);
+        assert_eq!(report.expected_points, 13.0 / 6.0);
+        assert_eq!(report.total_copies, 30);
+        let cascades: Vec<(usize, u64)> = report.attribution.iter().map(|a| (a.id, a.cascade)).collect();
+        assert_eq!(cascades, vec![(1, 15), (2, 7), (3, 4), (4, 2), (5, 1), (6, 1)]);
+        assert_eq!(report.attribution.iter().map(|a| a.cascade).sum::<u64>(), 30);
+        assert_eq!(report.longest_chain, vec![1, 2, 3, 4, 5]);
+    }
+}
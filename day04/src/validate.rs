@@ -0,0 +1,137 @@
+//! Checking a deck for cards that parse but do not look like a real pile.
+use std::collections::HashSet;
+use std::fmt;
+
+use crate::{ast, Card};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /// Suspicious, but the answers are still well defined.
+    Warning,
+    /// The card cannot be used, or part 2 would count the wrong cards.
+    Error,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Issue {
+    /// One based line number within the input.
+    pub line: usize,
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        write!(f, "line {}: {severity}: {}", self.line, self.message)
+    }
+}
+
+/// The first repeated value in `spans`, if any.
+fn duplicate<'a>(spans: impl Iterator<Item = &'a pest::Span<'a>>) -> Option<&'a str> {
+    let mut seen = HashSet::new();
+    spans.map(|span| span.as_str()).find(|n| !seen.insert(n.trim_start_matches('0')))
+}
+
+/// Parse every card in `input` and check that:
+///
+/// * ids count up from 1 without gaps, since part 2 wins copies by position,
+/// * no number appears twice in the winning list or in the hand,
+/// * every card has as many winning numbers and numbers as the first one.
+///
+/// Returns the cards that parsed along with every issue found. Blank lines at
+/// the end of the input are ignored.
+pub fn validate(input: &str) -> (Vec<Card>, Vec<Issue>) {
+    let mut cards = Vec::new();
+    let mut issues = Vec::new();
+    let mut columns = None;
+    let mut expected_id = 1;
+    let lines: Vec<&str> = input.lines().collect();
+    let last = lines.iter().rposition(|line| !line.trim().is_empty()).map_or(0, |i| i + 1);
+    for (i, line) in lines[..last].iter().enumerate() {
+        let line_number = i + 1;
+        let mut issue = |severity, message: String| {
+            issues.push(Issue { line: line_number, severity, message });
+        };
+        let parsed = ast::Card::parse(line.trim_end_matches('\r')).and_then(|ast| {
+            let card = Card::try_from(&ast)?;
+            Ok((ast, card))
+        });
+        let (ast, card) = match parsed {
+            Ok(parsed) => parsed,
+            Err(err) => {
+                issue(Severity::Error, err.to_string());
+                continue;
+            }
+        };
+        if card.id != expected_id {
+            issue(
+                Severity::Error,
+                format!("Card {} is out of sequence, expected card {expected_id}", card.id),
+            );
+        }
+        expected_id = card.id + 1;
+        if let Some(n) = duplicate(ast.winning.iter().map(|n| &n.span)) {
+            issue(Severity::Warning, format!("Winning number {n} is listed more than once"));
+        }
+        if let Some(n) = duplicate(ast.numbers.iter().map(|n| &n.span)) {
+            issue(Severity::Warning, format!("Number {n} is listed more than once"));
+        }
+        let shape = (ast.winning.len(), ast.numbers.len());
+        match columns {
+            None => columns = Some(shape),
+            Some(expected) if expected != shape => issue(
+                Severity::Warning,
+                format!(
+                    "Card {} has {} winning numbers and {} numbers, the first card has {} and {}",
+                    card.id, shape.0, shape.1, expected.0, expected.1,
+                ),
+            ),
+            Some(_) => {}
+        }
+        cards.push(card);
+    }
+    (cards, issues)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = include_str!("../example.txt");
+
+    #[test]
+    fn test_example_is_clean() {
+        let (cards, issues) = validate(EXAMPLE);
+        assert_eq!(cards.len(), 6);
+        assert_eq!(issues, vec![]);
+    }
+
+    #[test]
+    fn test_issues() {
+        let input = "Card 1: 1 2 | 3 4\n\
+                     Card 3: 1 1 | 3 4\n\
+                     Card 4: 1 2 | 3 03\n\
+                     Card 5: 1 2 | 3\n\
+                     Card six: 1 | 2\n\
+                     Card 6: 1 2 | 1 x\n";
+        let (cards, issues) = validate(input);
+        assert_eq!(cards.len(), 4);
+        let found: Vec<(usize, Severity)> = issues.iter().map(|i| (i.line, i.severity)).collect();
+        assert_eq!(
+            found,
+            vec![
+                (2, Severity::Error),
+                (2, Severity::Warning),
+                (3, Severity::Warning),
+                (4, Severity::Warning),
+                (5, Severity::Error),
+                (6, Severity::Error),
+            ],
+        );
+        assert_eq!(issues[0].to_string(), "line 2: error: Card 3 is out of sequence, expected card 2");
+    }
+}
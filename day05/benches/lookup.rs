@@ -0,0 +1,33 @@
+//! Looking up a million seeds through the seven maps in turn against a single
+//! precomputed seed to location map.
+use std::str::FromStr;
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use day05::Almanac;
+
+const INPUT: &str = include_str!("../input.txt");
+const SEEDS: usize = 1_000_000;
+
+fn lookup(c: &mut Criterion) {
+    let almanac = Almanac::from_str(INPUT).unwrap();
+    let ranges = almanac.seed_ranges().unwrap();
+    // Spread the seeds evenly over every seed range.
+    let per_range = SEEDS / ranges.len();
+    let seeds: Vec<usize> = ranges
+        .iter()
+        .flat_map(|range| range.clone().step_by((range.len() / per_range).max(1)).take(per_range))
+        .collect();
+
+    let mut group = c.benchmark_group("lookup");
+    let route = almanac.seed_to_location_route().unwrap();
+    group.bench_function("chain", |b| b.iter(|| seeds.iter().map(|seed| route.get(*seed)).min()));
+    let composed = almanac.seed_to_location_map().unwrap();
+    group.bench_function("composed", |b| {
+        b.iter(|| seeds.iter().map(|seed| composed.get(*seed)).min())
+    });
+    group.bench_function("compose", |b| b.iter(|| black_box(&almanac).seed_to_location_map()));
+    group.finish();
+}
+
+criterion_group!(benches, lookup);
+criterion_main!(benches);
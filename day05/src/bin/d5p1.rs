@@ -14,9 +14,10 @@ fn main() -> anyhow::Result<()> {
     let mut buffer = String::new();
     reader.read_to_string(&mut buffer)?;
     let almanac = Almanac::from_str(&buffer)?;
+    let route = almanac.seed_to_location_route()?;
     let mut locations = Vec::new();
     for seed in almanac.seeds.iter() {
-        let location = almanac.seed_to_location(*seed);
+        let location = route.get(*seed);
         locations.push(location);
     }
     println!("{}", locations.iter().min().unwrap());
@@ -9,8 +9,10 @@ use day05::Almanac;
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
 enum Version {
-    #[default] V1,
+    V1,
     V2,
+    #[default] Intervals,
+    Reverse,
 }
 
 impl FromStr for Version {
@@ -20,6 +22,8 @@ impl FromStr for Version {
         match s {
             "v1" => Ok(Version::V1),
             "v2" => Ok(Version::V2),
+            "intervals" => Ok(Version::Intervals),
+            "reverse" => Ok(Version::Reverse),
             _ => Err(anyhow::anyhow!("Invalid version")),
         }
     }
@@ -30,6 +34,9 @@ struct Args {
     filename: String,
     #[arg(long)]
     version: Option<Version>,
+    /// Fail on overlapping, empty, overflowing or colliding map entries
+    #[arg(long)]
+    strict: bool,
 }
 
 fn to_tuple<T>(slice: &[T]) -> (&T, &T) {
@@ -40,33 +47,52 @@ fn to_tuple<T>(slice: &[T]) -> (&T, &T) {
 }
 
 fn v1(almanac: &Almanac) -> anyhow::Result<usize> {
+    let route = almanac.seed_to_location_route()?;
     let ranges: Vec<_> = almanac.seeds.chunks(2).map(to_tuple).collect();
     let loc = ranges.into_iter().map(|(&start, length)| {
         (start..start+length).into_par_iter()
-            .map(|seed| almanac.seed_to_location(seed)).min().unwrap()
+            .map(|seed| route.get(seed)).min().unwrap()
     }).min().unwrap();
     Ok(loc)
 }
 
 fn v2(almanac: &Almanac) -> anyhow::Result<usize> {
+    let route = almanac.seed_to_location_route()?;
     let ranges: Vec<_> = almanac.seeds.chunks(2).map(to_tuple).collect();
     let loc = ranges.into_par_iter().map(|(&start, length)| {
         (start..start+length).into_par_iter()
-            .map(|seed| almanac.seed_to_location(seed)).min().unwrap()
+            .map(|seed| route.get(seed)).min().unwrap()
     }).min().unwrap();
     Ok(loc)
 }
 
+/// Push whole seed ranges through every map instead of single seeds.
+fn intervals(almanac: &Almanac) -> anyhow::Result<usize> {
+    let seeds = almanac.seed_ranges()?;
+    almanac.min_location(&seeds)?.ok_or_else(|| anyhow::anyhow!("No seeds"))
+}
+
+/// Walk the locations upwards until one can be reached from a seed.
+fn reverse(almanac: &Almanac) -> anyhow::Result<usize> {
+    let seeds = almanac.seed_ranges()?;
+    almanac.reverse_min_location(&seeds)?.ok_or_else(|| anyhow::anyhow!("No seeds"))
+}
+
 fn main() -> anyhow::Result<()> {
     let args = Args::parse();
     let f = File::open(args.filename)?;
     let mut reader = BufReader::new(f);
     let mut buffer = String::new();
     reader.read_to_string(&mut buffer)?;
-    let almanac = Almanac::from_str(&buffer)?;
+    let almanac = match args.strict {
+        true => Almanac::parse_strict(&buffer)?,
+        false => Almanac::from_str(&buffer)?,
+    };
     match args.version.unwrap_or_default() {
         Version::V1 => println!("{}", v1(&almanac)?),
         Version::V2 => println!("{}", v2(&almanac)?),
+        Version::Intervals => println!("{}", intervals(&almanac)?),
+        Version::Reverse => println!("{}", reverse(&almanac)?),
     }
     Ok(())
 }
@@ -1,82 +1,120 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt;
+use std::ops::Range;
 use std::str::FromStr;
+use interval::{IntervalMap, IntervalSet};
 use lazy_static::lazy_static;
 use regex::Regex;
 
+pub mod validate;
+
 lazy_static! {
-    static ref SEED_REGEX: Regex = Regex::new(r"^seeds: +(.*)").unwrap();
+    static ref SEED_REGEX: Regex = Regex::new(r"^seeds:(.*)$").unwrap();
     static ref MAP_REGEX: Regex = Regex::new(r"^((?<source>\w+)-to-(?<dest>\w+)) map:$").unwrap();
     static ref ENTRY_REGEX: Regex =
         Regex::new(r"^(?<dest>\d+) +(?<source>\d+) +(?<length>\d+)$").unwrap();
 }
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
-struct RangeMapEntry {
-    source: usize,
-    dest: usize,
-    length: usize,
-}
-
-impl PartialOrd for RangeMapEntry {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        Some(self.source.cmp(&other.source))
-    }
-}
-
-impl Ord for RangeMapEntry {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        self.source.cmp(&other.source)
-    }
-}
-
-#[derive(Debug, Clone, Default)]
+/// Moves ranges of values, leaving every value outside of them where it is.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub struct RangeMap {
-    list: Vec<RangeMapEntry>,
+    /// Each source range with the destination of its first value.
+    map: IntervalMap<usize, usize>,
+    /// Entries as pushed, `(source, dest, length)`, whose source or destination
+    /// ran past `usize::MAX` and were left out of `map`.
+    skipped: Vec<(usize, usize, usize)>,
 }
 
 impl RangeMap {
     pub fn new() -> Self {
-        RangeMap { list: Vec::new() }
+        RangeMap::default()
     }
 
+    /// Add the entry `dest source length`. An entry whose source or destination
+    /// range runs past `usize::MAX` maps nothing, and is kept only so that
+    /// [`RangeMap::validate`] can report it.
     pub fn push(&mut self, source: usize, dest: usize, length: usize) {
-        let entry = RangeMapEntry {
-            source,
-            dest,
-            length,
-        };
-        let index = self.list.partition_point(|x| x.source < source);
-        self.list.insert(index, entry);
+        if length > usize::MAX - source.max(dest) {
+            self.skipped.push((source, dest, length));
+            return;
+        }
+        self.map.insert(source..source + length, dest);
     }
 
-    #[cfg(test)]
-    fn get_entry(&self, i: usize) -> Option<&RangeMapEntry> {
-        Some(&self.list[i as usize])
+    /// Every entry as its source range and the destination of its first value,
+    /// by source.
+    pub fn entries(&self) -> impl Iterator<Item = (Range<usize>, usize)> + '_ {
+        self.map.entries().iter().cloned()
     }
 
     pub fn get(&self, i: usize) -> usize {
-        let index = self
-            .list
-            .binary_search_by(|entry| {
-                if entry.source <= i && i < entry.source + entry.length {
-                    std::cmp::Ordering::Equal
-                } else if entry.source > i {
-                    std::cmp::Ordering::Greater
-                } else {
-                    std::cmp::Ordering::Less
+        self.map.get(i).unwrap_or(i)
+    }
+
+    /// Map every value in `range`, which comes out as one range per entry or
+    /// identity gap it overlaps, in source order.
+    pub fn map_range(&self, range: Range<usize>) -> Vec<Range<usize>> {
+        self.map
+            .pieces(range)
+            .into_iter()
+            .map(|(piece, dest)| match dest {
+                Some(dest) => dest..dest + piece.len(),
+                None => piece,
+            })
+            .collect()
+    }
+
+    /// Map a set of ranges, returning the image as sorted, disjoint ranges.
+    pub fn map_ranges(&self, ranges: &[Range<usize>]) -> Vec<Range<usize>> {
+        let mapped = ranges.iter().flat_map(|range| self.map_range(range.clone())).collect();
+        IntervalSet::normalize(mapped)
+    }
+
+    /// A single map equivalent to applying `self` and then `other`. Values that
+    /// `self` leaves alone are still mapped by `other`, and values that end up
+    /// where they started are left out.
+    pub fn compose(&self, other: &RangeMap) -> RangeMap {
+        let mut entries: Vec<(Range<usize>, usize)> = Vec::new();
+        let mut source = 0;
+        for piece in self.map_range(0..usize::MAX) {
+            for mapped in other.map_range(piece) {
+                let length = mapped.len();
+                if mapped.start != source {
+                    // Merge into the previous entry when it continues both its
+                    // source and destination.
+                    match entries.last_mut() {
+                        Some((last, dest)) if last.end == source && *dest + last.len() == mapped.start => {
+                            last.end += length;
+                        }
+                        _ => entries.push((source..source + length, mapped.start)),
+                    }
                 }
-            });
-        match index {
-            Err(_) => i,
-            Ok(index) => {
-                let entry = self.list[index];
-                let delta = i - entry.source;
-                entry.dest + delta
+                source += length;
             }
         }
+        RangeMap { map: entries.into_iter().collect(), skipped: Vec::new() }
+    }
+
+    /// The map read backwards, from each output value to every input that
+    /// produces it.
+    pub fn invert(&self) -> InverseMap {
+        // Pieces rather than raw entries, so that where entries overlap only
+        // the one `get` uses is read backwards.
+        let mut pieces: Vec<Piece> = self
+            .map
+            .pieces(0..usize::MAX)
+            .into_iter()
+            .map(|(range, dest)| {
+                let dest = dest.unwrap_or(range.start);
+                Piece { dest: dest..dest + range.len(), source: range.start }
+            })
+            .collect();
+        pieces.sort_by_key(|piece| (piece.dest.start, piece.source));
+        InverseMap { pieces }
     }
 
     pub fn len(&self) -> usize {
-        self.list.len()
+        self.map.len()
     }
 
     pub fn is_empty(&self) -> bool {
@@ -84,51 +122,115 @@ impl RangeMap {
     }
 }
 
-pub struct Almanac {
-    pub seeds: Vec<usize>,
-    seed_to_soil: RangeMap,
-    soil_to_fertilizer: RangeMap,
-    fertilizer_to_water: RangeMap,
-    water_to_light: RangeMap,
-    light_to_temperature: RangeMap,
-    temperature_to_humidity: RangeMap,
-    humidity_to_location: RangeMap,
+/// Values `dest` that were produced from `source..source + dest.len()`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Piece {
+    dest: Range<usize>,
+    source: usize,
 }
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
-enum AlmanacMap {
-    SeedToSoil,
-    SoilToFertilizer,
-    FertilizerToWater,
-    WaterToLight,
-    LightToTemperature,
-    TemperatureToHumidity,
-    HumidityToLocation,
+impl Piece {
+    /// The inputs mapping onto the part of `range` this piece covers.
+    fn preimage(&self, range: &Range<usize>) -> Option<Range<usize>> {
+        let start = range.start.max(self.dest.start);
+        let end = range.end.min(self.dest.end);
+        (start < end).then(|| {
+            self.source + (start - self.dest.start)..self.source + (end - self.dest.start)
+        })
+    }
 }
 
-impl FromStr for AlmanacMap {
-    type Err = anyhow::Error;
+/// A [`RangeMap`] read backwards. Several entries can map onto the same
+/// values, so a value can have any number of preimages.
+#[derive(Debug, Clone)]
+pub struct InverseMap {
+    /// Every entry and identity gap of the original map, by destination.
+    pieces: Vec<Piece>,
+}
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s {
-            "seed-to-soil" => Ok(AlmanacMap::SeedToSoil),
-            "soil-to-fertilizer" => Ok(AlmanacMap::SoilToFertilizer),
-            "fertilizer-to-water" => Ok(AlmanacMap::FertilizerToWater),
-            "water-to-light" => Ok(AlmanacMap::WaterToLight),
-            "light-to-temperature" => Ok(AlmanacMap::LightToTemperature),
-            "temperature-to-humidity" => Ok(AlmanacMap::TemperatureToHumidity),
-            "humidity-to-location" => Ok(AlmanacMap::HumidityToLocation),
-            _ => Err(anyhow::anyhow!("Invalid map name")),
+impl InverseMap {
+    /// Every input mapping to a value in `range`, as sorted, disjoint ranges.
+    pub fn preimage(&self, range: Range<usize>) -> Vec<Range<usize>> {
+        let found = self.pieces.iter().filter_map(|piece| piece.preimage(&range)).collect();
+        IntervalSet::normalize(found)
+    }
+
+    /// The preimage of a set of ranges.
+    pub fn preimages(&self, ranges: &[Range<usize>]) -> Vec<Range<usize>> {
+        let found = ranges.iter().flat_map(|range| self.preimage(range.clone())).collect();
+        IntervalSet::normalize(found)
+    }
+
+    /// The lowest output with an input in `inputs`, found by walking the outputs
+    /// upwards one stretch between piece boundaries at a time.
+    pub fn first_reachable(&self, inputs: &[Range<usize>]) -> Option<usize> {
+        let mut bounds: Vec<usize> = self
+            .pieces
+            .iter()
+            .flat_map(|piece| [piece.dest.start, piece.dest.end])
+            .collect();
+        bounds.sort();
+        bounds.dedup();
+        for stretch in bounds.windows(2).map(|pair| pair[0]..pair[1]) {
+            let mut first = None;
+            for piece in &self.pieces {
+                let Some(preimage) = piece.preimage(&stretch) else {
+                    continue;
+                };
+                for input in inputs {
+                    let start = preimage.start.max(input.start);
+                    if start < preimage.end.min(input.end) {
+                        let output = stretch.start + (start - preimage.start);
+                        first = Some(first.map_or(output, |first: usize| first.min(output)));
+                    }
+                }
+            }
+            if first.is_some() {
+                return first;
+            }
         }
+        None
+    }
+}
+
+pub const SEED: &str = "seed";
+pub const LOCATION: &str = "location";
+
+/// The maps leading from one category to another, resolved once so that
+/// converting many values doesn't search the almanac for each of them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Route<'a> {
+    maps: Vec<&'a RangeMap>,
+}
+
+impl<'a> Route<'a> {
+    /// The maps in the order they are applied.
+    pub fn maps(&self) -> &[&'a RangeMap] {
+        &self.maps
     }
+
+    pub fn get(&self, value: usize) -> usize {
+        self.maps.iter().fold(value, |value, map| map.get(value))
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Almanac {
+    pub seeds: Vec<usize>,
+    /// The maps out of each category, keyed by source and then destination.
+    maps: BTreeMap<String, BTreeMap<String, RangeMap>>,
 }
 
 impl Almanac {
+    pub fn new(seeds: Vec<usize>) -> Self {
+        Almanac { seeds, maps: BTreeMap::new() }
+    }
+
     fn parse_seeds(line: &str) -> anyhow::Result<Vec<usize>> {
         let captures = SEED_REGEX
             .captures(line)
             .ok_or_else(|| anyhow::anyhow!("Invalid seeds line"))?;
-        let seeds_str = captures.get(1).unwrap().as_str();
+        let seeds_str = captures.get(1).map_or("", |m| m.as_str());
         let seeds = seeds_str
             .split_whitespace()
             .map(|x| x.parse::<usize>())
@@ -136,12 +238,13 @@ impl Almanac {
         Ok(seeds)
     }
 
-    fn parse_map(line: &str) -> anyhow::Result<AlmanacMap> {
+    fn parse_map(line: &str) -> anyhow::Result<(String, String)> {
         let captures = MAP_REGEX
             .captures(line)
             .ok_or_else(|| anyhow::anyhow!("Invalid map line"))?;
-        let name = captures.get(1).unwrap().as_str();
-        name.parse()
+        let source = captures.name("source").unwrap().as_str();
+        let dest = captures.name("dest").unwrap().as_str();
+        Ok((source.to_string(), dest.to_string()))
     }
 
     fn parse_entry(line: &str) -> anyhow::Result<(usize, usize, usize)> {
@@ -154,72 +257,275 @@ impl Almanac {
         Ok((dest, source, length))
     }
 
-    pub fn seed_to_location(&self, seed: usize) -> usize {
-        let soil = self.seed_to_soil.get(seed);
-        let fertilizer = self.soil_to_fertilizer.get(soil);
-        let water = self.fertilizer_to_water.get(fertilizer);
-        let light = self.water_to_light.get(water);
-        let temperature = self.light_to_temperature.get(light);
-        let humidity = self.temperature_to_humidity.get(temperature);
-        self.humidity_to_location.get(humidity)
+    /// Add or replace the map from category `from` to category `to`.
+    pub fn insert(&mut self, from: &str, to: &str, map: RangeMap) {
+        self.maps.entry(from.to_string()).or_default().insert(to.to_string(), map);
+    }
+
+    pub fn map(&self, from: &str, to: &str) -> Option<&RangeMap> {
+        self.maps.get(from)?.get(to)
+    }
+
+    /// Every category that is the source or destination of a map.
+    pub fn categories(&self) -> BTreeSet<&str> {
+        self.maps
+            .iter()
+            .flat_map(|(from, maps)| std::iter::once(from).chain(maps.keys()))
+            .map(String::as_str)
+            .collect()
+    }
+
+    /// The categories on the only route of maps from `from` to `to`, both
+    /// included. Fails if there is no route or more than one.
+    pub fn path<'a>(&'a self, from: &'a str, to: &str) -> anyhow::Result<Vec<&'a str>> {
+        let mut found: Vec<Vec<&str>> = Vec::new();
+        let mut path = vec![from];
+        self.find_paths(to, &mut path, &mut found);
+        match found.len() {
+            0 => Err(anyhow::anyhow!("No maps lead from {from} to {to}")),
+            1 => Ok(found.remove(0)),
+            _ => Err(anyhow::anyhow!(
+                "Ambiguous conversion from {from} to {to}: {} or {}",
+                found[0].join(" -> "),
+                found[1].join(" -> "),
+            )),
+        }
+    }
+
+    /// Depth first search for routes without repeated categories, stopping once
+    /// a second one is found.
+    fn find_paths<'a>(&'a self, to: &str, path: &mut Vec<&'a str>, found: &mut Vec<Vec<&'a str>>) {
+        let current = *path.last().unwrap();
+        if current == to {
+            found.push(path.clone());
+            return;
+        }
+        let Some(maps) = self.maps.get(current) else {
+            return;
+        };
+        for next in maps.keys() {
+            if found.len() > 1 {
+                return;
+            }
+            if !path.contains(&next.as_str()) {
+                path.push(next);
+                self.find_paths(to, path, found);
+                path.pop();
+            }
+        }
+    }
+
+    /// The maps to apply in order to convert from `from` to `to`.
+    pub fn route(&self, from: &str, to: &str) -> anyhow::Result<Route<'_>> {
+        let path = self.path(from, to)?;
+        let maps = path.windows(2).map(|pair| &self.maps[pair[0]][pair[1]]).collect();
+        Ok(Route { maps })
+    }
+
+    pub fn seed_to_location_route(&self) -> anyhow::Result<Route<'_>> {
+        self.route(SEED, LOCATION)
+    }
+
+    /// Convert a single `value` from category `from` to category `to`. Finding
+    /// the route is the slow part, so resolve it once with
+    /// [`Almanac::route`] to convert many values.
+    pub fn convert(&self, value: usize, from: &str, to: &str) -> anyhow::Result<usize> {
+        Ok(self.route(from, to)?.get(value))
+    }
+
+    /// A single seed's location, see [`Almanac::seed_to_location_route`] for
+    /// many seeds.
+    pub fn seed_to_location(&self, seed: usize) -> anyhow::Result<usize> {
+        self.convert(seed, SEED, LOCATION)
+    }
+
+    /// Every map folded into one, so a seed's location takes a single lookup.
+    pub fn seed_to_location_map(&self) -> anyhow::Result<RangeMap> {
+        Ok(self
+            .seed_to_location_route()?
+            .maps()
+            .iter()
+            .fold(RangeMap::new(), |composed, map| composed.compose(map)))
+    }
+
+    /// Every seed ending up at `location`.
+    pub fn location_to_seeds(&self, location: usize) -> anyhow::Result<Vec<Range<usize>>> {
+        let location = std::iter::once(location..location + 1).collect();
+        Ok(self
+            .seed_to_location_route()?
+            .maps()
+            .iter()
+            .rev()
+            .fold(location, |ranges, map| map.invert().preimages(&ranges)))
+    }
+
+    /// The lowest location of any seed in `seeds`, searching backwards from the
+    /// locations instead of forwards from the seeds.
+    pub fn reverse_min_location(&self, seeds: &[Range<usize>]) -> anyhow::Result<Option<usize>> {
+        Ok(self.seed_to_location_map()?.invert().first_reachable(seeds))
+    }
+
+    /// The seeds line read as `start length` pairs, as in part 2.
+    pub fn seed_ranges(&self) -> anyhow::Result<Vec<Range<usize>>> {
+        if !self.seeds.len().is_multiple_of(2) {
+            anyhow::bail!("Expected seed ranges in pairs, got {} numbers", self.seeds.len());
+        }
+        self.seeds
+            .chunks(2)
+            .map(|pair| match pair[0].checked_add(pair[1]) {
+                Some(end) => Ok(pair[0]..end),
+                None => Err(anyhow::anyhow!("Seed range {} {} runs past {}", pair[0], pair[1], usize::MAX)),
+            })
+            .collect()
+    }
+
+    /// The locations of every seed in `seeds`, as sorted, disjoint ranges.
+    pub fn ranges_to_locations(&self, seeds: &[Range<usize>]) -> anyhow::Result<Vec<Range<usize>>> {
+        Ok(self
+            .seed_to_location_route()?
+            .maps()
+            .iter()
+            .fold(IntervalSet::normalize(seeds.to_vec()), |ranges, map| map.map_ranges(&ranges)))
+    }
+
+    /// The lowest location of any seed in `seeds`.
+    pub fn min_location(&self, seeds: &[Range<usize>]) -> anyhow::Result<Option<usize>> {
+        Ok(self.ranges_to_locations(seeds)?.first().map(|range| range.start))
     }
 }
 
+/// Where the parser is within an almanac.
+enum Section {
+    /// Before the seeds line.
+    Start,
+    /// After the seeds line or a blank line, expecting a map header.
+    Between,
+    /// Inside a map, expecting its entries.
+    Map(String, String),
+}
+
+/// Parses an almanac section by section. Every line must be what the current
+/// section allows, and each map may only appear once.
 impl FromStr for Almanac {
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut almanac = Almanac {
-            seeds: Vec::new(),
-            seed_to_soil: RangeMap::new(),
-            soil_to_fertilizer: RangeMap::new(),
-            fertilizer_to_water: RangeMap::new(),
-            water_to_light: RangeMap::new(),
-            light_to_temperature: RangeMap::new(),
-            temperature_to_humidity: RangeMap::new(),
-            humidity_to_location: RangeMap::new(),
-        };
-        let mut current_map = None;
-        for line in s.lines() {
-            if let Ok(seeds) = Almanac::parse_seeds(line) {
-                almanac.seeds = seeds;
+        let mut almanac = Almanac::default();
+        let mut headers: HashMap<(String, String), usize> = HashMap::new();
+        let mut section = Section::Start;
+        for (i, line) in s.lines().enumerate() {
+            let number = i + 1;
+            let line = line.trim_end();
+            if line.is_empty() {
+                if let Section::Map(..) = section {
+                    section = Section::Between;
+                }
+                continue;
             }
-            if let Ok(new_map_section) = Almanac::parse_map(line) {
-                current_map = Some(new_map_section);
+            if let Section::Start = section {
+                almanac.seeds = Almanac::parse_seeds(line).map_err(|_| {
+                    anyhow::anyhow!("Line {number}: expected `seeds: ...`, found {line:?}")
+                })?;
+                section = Section::Between;
+                continue;
             }
-            if let Ok((dest, source, length)) = Almanac::parse_entry(line) {
-                match current_map {
-                    Some(AlmanacMap::SeedToSoil) => almanac.seed_to_soil.push(source, dest, length),
-                    Some(AlmanacMap::SoilToFertilizer) => {
-                        almanac.soil_to_fertilizer.push(source, dest, length)
-                    }
-                    Some(AlmanacMap::FertilizerToWater) => {
-                        almanac.fertilizer_to_water.push(source, dest, length)
-                    }
-                    Some(AlmanacMap::WaterToLight) => {
-                        almanac.water_to_light.push(source, dest, length)
-                    }
-                    Some(AlmanacMap::LightToTemperature) => {
-                        almanac.light_to_temperature.push(source, dest, length)
-                    }
-                    Some(AlmanacMap::TemperatureToHumidity) => {
-                        almanac.temperature_to_humidity.push(source, dest, length)
-                    }
-                    Some(AlmanacMap::HumidityToLocation) => {
-                        almanac.humidity_to_location.push(source, dest, length)
-                    }
-                    None => unreachable!(),
+            if SEED_REGEX.is_match(line) {
+                anyhow::bail!("Line {number}: duplicate seeds line");
+            }
+            if let Ok((from, to)) = Almanac::parse_map(line) {
+                let key = (from.clone(), to.clone());
+                if let Some(first) = headers.insert(key, number) {
+                    anyhow::bail!(
+                        "Line {number}: duplicate {from}-to-{to} map, first seen on line {first}"
+                    );
                 }
+                almanac.insert(&from, &to, RangeMap::new());
+                section = Section::Map(from, to);
+                continue;
             }
+            let Section::Map(from, to) = &section else {
+                anyhow::bail!("Line {number}: expected a map header like `seed-to-soil map:`, found {line:?}");
+            };
+            let (dest, source, length) = Almanac::parse_entry(line).map_err(|err| {
+                anyhow::anyhow!(
+                    "Line {number}: expected an entry like `50 98 2` in the {from}-to-{to} map, \
+                     found {line:?} ({err})"
+                )
+            })?;
+            let map = almanac.maps.get_mut(from).unwrap().get_mut(to).unwrap();
+            map.push(source, dest, length);
+        }
+        if let Section::Start = section {
+            anyhow::bail!("Missing `seeds: ...` line");
         }
         Ok(almanac)
     }
 }
 
+impl Almanac {
+    /// Every map as `(from, to)`, with a map's source category printed before
+    /// any map into it whenever the maps have no cycle.
+    fn sections(&self) -> Vec<(&str, &str)> {
+        let mut incoming: BTreeMap<&str, usize> =
+            self.categories().into_iter().map(|category| (category, 0)).collect();
+        for maps in self.maps.values() {
+            for to in maps.keys() {
+                *incoming.get_mut(to.as_str()).unwrap() += 1;
+            }
+        }
+        let mut ready: BTreeSet<&str> =
+            incoming.iter().filter(|(_, n)| **n == 0).map(|(c, _)| *c).collect();
+        let mut done: BTreeSet<&str> = BTreeSet::new();
+        let mut sections = Vec::new();
+        loop {
+            let next = ready.pop_first().or_else(|| {
+                // Only cycles are left, break into them at the first category.
+                self.maps.keys().map(String::as_str).find(|c| !done.contains(c))
+            });
+            let Some(from) = next else {
+                break;
+            };
+            done.insert(from);
+            for to in self.maps.get(from).into_iter().flat_map(|maps| maps.keys()) {
+                sections.push((from, to.as_str()));
+                let n = incoming.get_mut(to.as_str()).unwrap();
+                *n = n.saturating_sub(1);
+                if *n == 0 && !done.contains(to.as_str()) {
+                    ready.insert(to);
+                }
+            }
+        }
+        sections
+    }
+}
+
+/// The canonical text of the almanac: single spaces, one blank line between
+/// sections, entries by source followed by any that were skipped, and maps in
+/// dependency order.
+impl fmt::Display for Almanac {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let seeds: Vec<String> = self.seeds.iter().map(|seed| seed.to_string()).collect();
+        writeln!(f, "seeds: {}", seeds.join(" "))?;
+        for (from, to) in self.sections() {
+            writeln!(f)?;
+            writeln!(f, "{from}-to-{to} map:")?;
+            let map = &self.maps[from][to];
+            for (source, dest) in map.entries() {
+                writeln!(f, "{dest} {} {}", source.start, source.len())?;
+            }
+            for (source, dest, length) in &map.skipped {
+                writeln!(f, "{dest} {source} {length}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     #[allow(unused_imports)]
     use super::*;
+    use proptest::prelude::*;
 
     const EXAMPLE: &str = include_str!("../example.txt");
 
@@ -234,7 +540,7 @@ mod tests {
     fn test_parse_map() {
         let line = "soil-to-fertilizer map:";
         let map = Almanac::parse_map(line).unwrap();
-        assert_eq!(map, AlmanacMap::SoilToFertilizer);
+        assert_eq!(map, ("soil".to_string(), "fertilizer".to_string()));
     }
 
     #[test]
@@ -250,24 +556,265 @@ mod tests {
     fn test_parse_example() {
         let almanac = Almanac::from_str(EXAMPLE).unwrap();
         assert_eq!(almanac.seeds, vec![79, 14, 55, 13]);
-        assert_eq!(almanac.seed_to_soil.len(), 2);
-        assert_eq!(almanac.seed_to_soil.get_entry(0).unwrap().source, 50);
-        assert_eq!(almanac.seed_to_soil.get_entry(0).unwrap().dest, 52);
-        assert_eq!(almanac.seed_to_soil.get_entry(0).unwrap().length, 48);
-        assert_eq!(almanac.soil_to_fertilizer.len(), 3);
-        assert_eq!(almanac.fertilizer_to_water.len(), 4);
-        assert_eq!(almanac.water_to_light.len(), 2);
-        assert_eq!(almanac.light_to_temperature.len(), 3);
-        assert_eq!(almanac.temperature_to_humidity.len(), 2);
-        assert_eq!(almanac.humidity_to_location.len(), 2);
+        let seed_to_soil = almanac.map("seed", "soil").unwrap();
+        assert_eq!(seed_to_soil.len(), 2);
+        assert_eq!(seed_to_soil.entries().next(), Some((50..98, 52)));
+        assert_eq!(almanac.map("soil", "fertilizer").unwrap().len(), 3);
+        assert_eq!(almanac.map("fertilizer", "water").unwrap().len(), 4);
+        assert_eq!(almanac.map("water", "light").unwrap().len(), 2);
+        assert_eq!(almanac.map("light", "temperature").unwrap().len(), 3);
+        assert_eq!(almanac.map("temperature", "humidity").unwrap().len(), 2);
+        assert_eq!(almanac.map("humidity", "location").unwrap().len(), 2);
+        assert_eq!(almanac.categories().len(), 8);
+    }
+
+    #[test]
+    fn test_map_range() {
+        let mut map = RangeMap::new();
+        map.push(98, 50, 2);
+        map.push(50, 52, 48);
+        assert_eq!(map.map_range(40..60), vec![40..50, 52..62]);
+        assert_eq!(map.map_range(95..105), vec![97..100, 50..52, 100..105]);
+        assert_eq!(map.map_range(0..10), vec![0..10]);
+        assert_eq!(map.map_ranges(&[0..10, 97..99, 5..15]), vec![0..15, 50..51, 99..100]);
+    }
+
+    #[test]
+    fn test_example_min_location() {
+        let almanac = Almanac::from_str(EXAMPLE).unwrap();
+        let seeds = almanac.seed_ranges().unwrap();
+        assert_eq!(almanac.min_location(&seeds).unwrap(), Some(46));
+    }
+
+    #[test]
+    fn test_seed_ranges() {
+        let almanac = Almanac::new(vec![79, 14, 55, 13]);
+        assert_eq!(almanac.seed_ranges().unwrap(), vec![79..93, 55..68]);
+        assert!(Almanac::new(vec![79, 14, 55]).seed_ranges().is_err());
+        let err = Almanac::new(vec![usize::MAX, 1]).seed_ranges().unwrap_err();
+        assert!(err.to_string().starts_with(&format!("Seed range {} 1 runs past", usize::MAX)));
+        assert_eq!(Almanac::new(vec![usize::MAX, 0]).seed_ranges().unwrap(), vec![usize::MAX..usize::MAX]);
+    }
+
+    const CATEGORIES: [&str; 8] =
+        ["seed", "soil", "fertilizer", "water", "light", "temperature", "humidity", "location"];
+
+    fn range_map() -> impl Strategy<Value = RangeMap> {
+        // Sources may overlap, which `get` resolves by the later start.
+        prop::collection::vec((0..100usize, 0..15usize, 0..100usize), 0..5).prop_map(|entries| {
+            let mut map = RangeMap::new();
+            for (source, length, dest) in entries {
+                map.push(source, dest, length);
+            }
+            map
+        })
+    }
+
+    fn almanac() -> impl Strategy<Value = Almanac> {
+        (prop::collection::vec((0..120usize, 0..30usize), 0..4), prop::array::uniform7(range_map()))
+            .prop_map(|(seeds, maps)| {
+                let seeds = seeds.into_iter().flat_map(|(start, length)| [start, length]).collect();
+                let mut almanac = Almanac::new(seeds);
+                for (pair, map) in CATEGORIES.windows(2).zip(maps) {
+                    almanac.insert(pair[0], pair[1], map);
+                }
+                almanac
+            })
+    }
+
+    #[test]
+    fn test_compose() {
+        let almanac = Almanac::from_str(EXAMPLE).unwrap();
+        let map = almanac.seed_to_location_map().unwrap();
+        for seed in 0..200 {
+            assert_eq!(map.get(seed), almanac.seed_to_location(seed).unwrap(), "seed {seed}");
+        }
+        let mut shift = RangeMap::new();
+        shift.push(0, 10, 5);
+        let mut back = RangeMap::new();
+        back.push(10, 0, 5);
+        back.push(20, 30, 2);
+        let composed = shift.compose(&back);
+        // 0..5 goes to 10..15 and straight back, while 10..15 and 20..22 are
+        // only moved by the second map.
+        assert_eq!(composed.len(), 2);
+        assert_eq!(composed.get(3), 3);
+        assert_eq!(composed.get(12), 2);
+        assert_eq!(composed.get(21), 31);
+        assert_eq!(composed.get(22), 22);
+    }
+
+    #[test]
+    fn test_invert() {
+        let mut map = RangeMap::new();
+        map.push(98, 50, 2);
+        map.push(50, 52, 48);
+        let inverse = map.invert();
+        // 50 is reached from 98 and 52 is reached from 50, but both are also
+        // mapped away themselves.
+        assert_eq!(inverse.preimage(50..53), vec![50..51, 98..100]);
+        assert_eq!(inverse.preimage(99..100), vec![97..98]);
+        assert_eq!(inverse.preimage(10..12), vec![10..12]);
+        assert_eq!(inverse.preimage(100..101), vec![100..101]);
+        // Two entries onto the same values.
+        let mut merge = RangeMap::new();
+        merge.push(0, 100, 10);
+        merge.push(20, 100, 10);
+        assert_eq!(merge.invert().preimage(105..106), vec![5..6, 25..26, 105..106]);
+        // Where entries overlap, only the later start maps.
+        let mut shadow = RangeMap::new();
+        shadow.push(0, 100, 10);
+        shadow.push(5, 200, 2);
+        assert_eq!(shadow.get(5), 200);
+        assert_eq!(shadow.invert().preimage(105..106), vec![105..106]);
+        assert_eq!(shadow.invert().preimage(200..201), vec![5..6, 200..201]);
+    }
+
+    #[test]
+    fn test_example_reverse() {
+        let almanac = Almanac::from_str(EXAMPLE).unwrap();
+        assert_eq!(almanac.location_to_seeds(82).unwrap(), vec![79..80]);
+        let seeds = almanac.seed_ranges().unwrap();
+        assert_eq!(almanac.reverse_min_location(&seeds).unwrap(), Some(46));
+    }
+
+    proptest! {
+        #[test]
+        fn test_inverse_matches_points(almanac in almanac(), location in 0..200usize) {
+            let expected: Vec<usize> = (0..400)
+                .filter(|seed| almanac.seed_to_location(*seed).unwrap() == location)
+                .collect();
+            let seeds: Vec<usize> = almanac
+                .location_to_seeds(location)
+                .unwrap()
+                .into_iter()
+                .flatten()
+                .take_while(|seed| *seed < 400)
+                .collect();
+            prop_assert_eq!(seeds, expected);
+            let ranges = almanac.seed_ranges().unwrap();
+            prop_assert_eq!(almanac.reverse_min_location(&ranges).unwrap(), almanac.min_location(&ranges).unwrap());
+        }
+
+        #[test]
+        fn test_compose_matches_chain(almanac in almanac()) {
+            let map = almanac.seed_to_location_map().unwrap();
+            for seed in 0..200 {
+                prop_assert_eq!(map.get(seed), almanac.seed_to_location(seed).unwrap());
+            }
+        }
+
+        #[test]
+        fn test_ranges_match_points(almanac in almanac()) {
+            let seeds = almanac.seed_ranges().unwrap();
+            let mut expected: Vec<usize> = seeds
+                .iter()
+                .flat_map(|range| range.clone())
+                .map(|seed| almanac.seed_to_location(seed).unwrap())
+                .collect();
+            expected.sort();
+            expected.dedup();
+            let locations: Vec<usize> = almanac
+                .ranges_to_locations(&seeds)
+                .unwrap()
+                .into_iter()
+                .flatten()
+                .collect();
+            prop_assert_eq!(almanac.min_location(&seeds).unwrap(), expected.first().copied());
+            prop_assert_eq!(locations, expected);
+        }
+    }
+
+    #[test]
+    fn test_convert() {
+        let almanac = Almanac::from_str(EXAMPLE).unwrap();
+        assert_eq!(almanac.convert(79, "seed", "soil").unwrap(), 81);
+        assert_eq!(almanac.convert(81, "soil", "location").unwrap(), 82);
+        assert_eq!(almanac.convert(79, "seed", "seed").unwrap(), 79);
+        assert!(almanac.convert(82, "location", "seed").is_err());
+        assert!(almanac.convert(1, "seed", "nowhere").is_err());
+        assert_eq!(almanac.path("water", "location").unwrap().len(), 5);
+    }
+
+    #[test]
+    fn test_custom_categories() {
+        let input = "seeds: 1 2\n\n\
+                     seed-to-soil map:\n5 0 10\n\n\
+                     soil-to-flavor map:\n0 5 3\n\n\
+                     seed-to-flavor map:\n100 0 1\n";
+        let mut almanac = Almanac::from_str(input).unwrap();
+        assert_eq!(almanac.categories().into_iter().collect::<Vec<_>>(), vec!["flavor", "seed", "soil"]);
+        assert_eq!(almanac.convert(1, "seed", "soil").unwrap(), 6);
+        let err = almanac.convert(1, "seed", "flavor").unwrap_err();
+        assert!(err.to_string().contains("Ambiguous"), "{err}");
+        almanac.insert("seed", "flavor", RangeMap::new());
+        assert!(almanac.convert(1, "seed", "flavor").is_err());
+        let mut chain = Almanac::new(vec![]);
+        chain.insert("soil", "flavor", almanac.map("soil", "flavor").unwrap().clone());
+        chain.insert("seed", "soil", almanac.map("seed", "soil").unwrap().clone());
+        assert_eq!(chain.convert(1, "seed", "flavor").unwrap(), 1);
+        assert_eq!(chain.convert(3, "seed", "flavor").unwrap(), 8);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let error = |input: &str| Almanac::from_str(input).unwrap_err().to_string();
+        assert!(error("").contains("Missing"));
+        assert!(error("50 98 2\n").starts_with("Line 1: expected `seeds: ...`"));
+        assert!(error("seeds: 1\n\n50 98 2\n").starts_with("Line 3: expected a map header"));
+        assert!(error("seeds: 1\nseeds: 2\n").starts_with("Line 2: duplicate seeds"));
+        let duplicate = "seeds: 1\n\nseed-to-soil map:\n1 2 3\n\nseed-to-soil map:\n4 5 6\n";
+        assert_eq!(error(duplicate), "Line 6: duplicate seed-to-soil map, first seen on line 3");
+        let junk = "seeds: 1\n\nseed-to-soil map:\n1 2 3\n1 2\n";
+        assert!(error(junk).starts_with("Line 5: expected an entry like `50 98 2` in the seed-to-soil map"));
+        let overflow = "seeds: 1\n\nseed-to-soil map:\n1 2 99999999999999999999\n";
+        assert!(error(overflow).starts_with("Line 4:"));
+    }
+
+    #[test]
+    fn test_skips_overflow() {
+        let past_max = format!("seeds: 1\n\nseed-to-soil map:\n0 {} 2\n7 5 1\n", usize::MAX - 1);
+        let almanac = Almanac::from_str(&past_max).unwrap();
+        let map = almanac.map("seed", "soil").unwrap();
+        assert_eq!(map.len(), 1);
+        assert_eq!(map.get(usize::MAX - 1), usize::MAX - 1);
+        assert_eq!(map.get(5), 7);
+        let text = almanac.to_string();
+        assert!(text.ends_with(&format!("7 5 1\n0 {} 2\n", usize::MAX - 1)));
+        assert_eq!(Almanac::from_str(&text).unwrap(), almanac);
+        let mut map = RangeMap::new();
+        map.push(5, usize::MAX - 1, 2);
+        map.push(5, usize::MAX - 1, 1);
+        assert_eq!(map.len(), 1);
+    }
+
+    #[test]
+    fn test_display_round_trip() {
+        let almanac = Almanac::from_str(EXAMPLE).unwrap();
+        let text = almanac.to_string();
+        assert!(text.starts_with("seeds: 79 14 55 13\n\nseed-to-soil map:\n52 50 48\n50 98 2\n\nsoil-to-fertilizer map:\n"));
+        assert!(text.ends_with("humidity-to-location map:\n60 56 37\n56 93 4\n"));
+        let reparsed = Almanac::from_str(&text).unwrap();
+        assert_eq!(reparsed, almanac);
+        assert_eq!(reparsed.to_string(), text);
+    }
+
+    proptest! {
+        #[test]
+        fn test_display_round_trip_random(almanac in almanac()) {
+            let text = almanac.to_string();
+            let reparsed = Almanac::from_str(&text).unwrap();
+            prop_assert_eq!(reparsed.to_string(), text);
+            prop_assert_eq!(reparsed, almanac);
+        }
     }
 
     #[test]
     fn test_example_seed_to_location() {
         let almanac = Almanac::from_str(EXAMPLE).unwrap();
-        assert_eq!(almanac.seed_to_location(79), 82);
-        assert_eq!(almanac.seed_to_location(14), 43);
-        assert_eq!(almanac.seed_to_location(55), 86);
-        assert_eq!(almanac.seed_to_location(13), 35);
+        assert_eq!(almanac.seed_to_location(79).unwrap(), 82);
+        assert_eq!(almanac.seed_to_location(14).unwrap(), 43);
+        assert_eq!(almanac.seed_to_location(55).unwrap(), 86);
+        assert_eq!(almanac.seed_to_location(13).unwrap(), 35);
     }
 }
@@ -0,0 +1,175 @@
+//! Integrity checks for range maps.
+//!
+//! A well formed map has non-empty entries whose source ranges do not overlap,
+//! so every value has at most one entry, and whose destinations do not collide
+//! with each other or with the values left alone, so no two values end up in
+//! the same place.
+use std::fmt;
+use std::ops::Range;
+
+use interval::{overlaps, IntervalSet};
+
+use crate::{Almanac, RangeMap};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Problem {
+    /// Two entries both claim the `sources` values.
+    Overlap { sources: Range<usize> },
+    /// An entry with a length of zero, which maps nothing.
+    ZeroLength { source: usize, dest: usize },
+    /// An entry whose source or destination range runs past `usize::MAX`,
+    /// which was skipped.
+    Overflow { source: usize, dest: usize, length: usize },
+    /// The `dests` values are produced by two entries, or by an entry and by
+    /// values that no entry moves.
+    Collision { dests: Range<usize>, identity: bool },
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Problem::Overlap { sources } => write!(f, "entries overlap on sources {sources:?}"),
+            Problem::ZeroLength { source, dest } => {
+                write!(f, "entry {dest} {source} 0 has a length of zero")
+            }
+            Problem::Overflow { source, dest, length } => {
+                write!(f, "entry {dest} {source} {length} runs past {}", usize::MAX)
+            }
+            Problem::Collision { dests, identity: false } => {
+                write!(f, "entries collide on destinations {dests:?}")
+            }
+            Problem::Collision { dests, identity: true } => {
+                write!(f, "an entry maps onto unmapped values {dests:?}")
+            }
+        }
+    }
+}
+
+/// Which source values a map moves and which it leaves alone.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Coverage {
+    pub mapped: Vec<Range<usize>>,
+    pub identity: Vec<Range<usize>>,
+}
+
+impl Coverage {
+    /// How many values are moved by some entry.
+    pub fn mapped_len(&self) -> usize {
+        self.mapped.iter().map(|range| range.len()).sum()
+    }
+}
+
+impl fmt::Display for Coverage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let list = |ranges: &[Range<usize>]| {
+            ranges.iter().map(|r| format!("{r:?}")).collect::<Vec<_>>().join(", ")
+        };
+        writeln!(f, "mapped ({} values): {}", self.mapped_len(), list(&self.mapped))?;
+        write!(f, "identity: {}", list(&self.identity))
+    }
+}
+
+impl RangeMap {
+    pub fn coverage(&self) -> Coverage {
+        let mapped = self.map.domain();
+        let identity = mapped.complement(0..usize::MAX).into_ranges();
+        Coverage { mapped: mapped.into_ranges(), identity }
+    }
+
+    /// Every problem with the entries of this map, see [`Problem`].
+    pub fn validate(&self) -> Vec<Problem> {
+        let mut problems = Vec::new();
+        for (sources, dest) in self.entries() {
+            if sources.is_empty() {
+                problems.push(Problem::ZeroLength { source: sources.start, dest });
+            }
+        }
+        for &(source, dest, length) in &self.skipped {
+            problems.push(Problem::Overflow { source, dest, length });
+        }
+        for sources in self.map.overlaps() {
+            problems.push(Problem::Overlap { sources });
+        }
+        let dests: Vec<Range<usize>> =
+            self.entries().map(|(sources, dest)| dest..dest + sources.len()).collect();
+        for dests in overlaps(dests.clone()) {
+            problems.push(Problem::Collision { dests, identity: false });
+        }
+        let identity = self.map.domain().complement(0..usize::MAX);
+        for dests in IntervalSet::from(dests).intersection(&identity).into_ranges() {
+            problems.push(Problem::Collision { dests, identity: true });
+        }
+        problems
+    }
+}
+
+impl Almanac {
+    /// The problems with every map, each with the name of its section.
+    pub fn validate(&self) -> Vec<(String, Problem)> {
+        self.maps
+            .iter()
+            .flat_map(|(from, maps)| maps.iter().map(move |(to, map)| (from, to, map)))
+            .flat_map(|(from, to, map)| {
+                map.validate().into_iter().map(move |p| (format!("{from}-to-{to}"), p))
+            })
+            .collect()
+    }
+
+    /// Parse like `from_str`, but fail if any map has a problem.
+    pub fn parse_strict(s: &str) -> anyhow::Result<Almanac> {
+        let almanac: Almanac = s.parse()?;
+        let problems = almanac.validate();
+        if !problems.is_empty() {
+            let lines: Vec<String> = problems
+                .iter()
+                .map(|(name, problem)| format!("{name} map: {problem}"))
+                .collect();
+            anyhow::bail!("Invalid almanac:\n{}", lines.join("\n"));
+        }
+        Ok(almanac)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = include_str!("../example.txt");
+
+    #[test]
+    fn test_example_is_valid() {
+        let almanac = Almanac::parse_strict(EXAMPLE).unwrap();
+        let coverage = almanac.map("seed", "soil").unwrap().coverage();
+        assert_eq!(coverage.mapped, vec![50..100]);
+        assert_eq!(coverage.identity, vec![0..50, 100..usize::MAX]);
+    }
+
+    #[test]
+    fn test_problems() {
+        let mut map = RangeMap::new();
+        map.push(0, 100, 10);
+        map.push(5, 200, 10);
+        map.push(20, 0, 0);
+        map.push(30, 105, 2);
+        map.push(40, 50, 5);
+        map.push(usize::MAX - 1, 0, 2);
+        assert_eq!(
+            map.validate(),
+            vec![
+                Problem::ZeroLength { source: 20, dest: 0 },
+                Problem::Overflow { source: usize::MAX - 1, dest: 0, length: 2 },
+                Problem::Overlap { sources: 5..10 },
+                Problem::Collision { dests: 105..107, identity: false },
+                Problem::Collision { dests: 50..55, identity: true },
+                Problem::Collision { dests: 100..110, identity: true },
+                Problem::Collision { dests: 200..210, identity: true },
+            ],
+        );
+        assert_eq!(Problem::Overlap { sources: 5..10 }.to_string(), "entries overlap on sources 5..10");
+        let past_max = format!("seeds: 1\n\nseed-to-soil map:\n0 {} 2\n", usize::MAX - 1);
+        assert_eq!(
+            Almanac::parse_strict(&past_max).unwrap_err().to_string(),
+            format!("Invalid almanac:\nseed-to-soil map: entry 0 {} 2 runs past {}", usize::MAX - 1, usize::MAX),
+        );
+    }
+}
@@ -0,0 +1,96 @@
+use std::str::FromStr;
+use clap::Parser;
+
+use day06::model::{Drag, Piecewise, SpeedCap};
+use day06::{solve, BoatModel, Linear, Mode, Races};
+
+/// A boat model given as `linear`, `cap:N`, `drag:N` or
+/// `piecewise:DURATIONxRATE,...`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Model {
+    Linear,
+    SpeedCap(u64),
+    Drag(u64),
+    Piecewise(Vec<(u64, u64)>),
+}
+
+impl FromStr for Model {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, params) = s.split_once(':').unwrap_or((s, ""));
+        match name {
+            "linear" => Ok(Model::Linear),
+            "cap" => Ok(Model::SpeedCap(params.parse()?)),
+            "drag" => Ok(Model::Drag(params.parse()?)),
+            "piecewise" => {
+                let segments = params
+                    .split(',')
+                    .map(|segment| {
+                        let (duration, rate) = segment
+                            .split_once('x')
+                            .ok_or_else(|| anyhow::anyhow!("Invalid segment {segment:?}"))?;
+                        Ok((duration.parse()?, rate.parse()?))
+                    })
+                    .collect::<anyhow::Result<_>>()?;
+                Ok(Model::Piecewise(segments))
+            }
+            _ => Err(anyhow::anyhow!("Invalid model")),
+        }
+    }
+}
+
+impl Model {
+    fn build(&self) -> anyhow::Result<Box<dyn BoatModel>> {
+        Ok(match self {
+            Model::Linear => Box::new(Linear),
+            Model::SpeedCap(cap) => Box::new(SpeedCap { cap: *cap }),
+            Model::Drag(drag) => Box::new(Drag { drag: *drag }),
+            Model::Piecewise(segments) => Box::new(Piecewise::new(segments.clone())?),
+        })
+    }
+}
+
+#[derive(Debug, Parser)]
+struct Args {
+    #[arg(default_value = "input.txt")]
+    filename: String,
+    #[arg(long, default_value = "linear")]
+    model: Model,
+    /// Read each row as one number, as in part 2
+    #[arg(long)]
+    kerned: bool,
+}
+
+fn main() -> anyhow::Result<()> {
+    let args = Args::parse();
+    let input = std::fs::read_to_string(&args.filename)?;
+    let mode = if args.kerned { Mode::Kerned } else { Mode::Spaced };
+    let races = Races::parse(&input, mode)?.races()?;
+    let model = args.model.build()?;
+    let mut product: u128 = 1;
+    for (i, race) in races.iter().enumerate() {
+        let solution = solve(model.as_ref(), race);
+        println!("Race {}: {solution}", i + 1);
+        product = product
+            .checked_mul(solution.margin() as u128)
+            .ok_or_else(|| anyhow::anyhow!("The product of the margins overflows at race {}", i + 1))?;
+    }
+    println!("Solution: {product}");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    #[allow(unused_imports)]
+    use super::*;
+
+    #[test]
+    fn test_parse_model() {
+        assert_eq!("cap:12".parse::<Model>().unwrap(), Model::SpeedCap(12));
+        let staged = "piecewise:5x4,10x1".parse::<Model>().unwrap();
+        assert_eq!(staged, Model::Piecewise(vec![(5, 4), (10, 1)]));
+        assert!("piecewise:5".parse::<Model>().is_err());
+        assert!("warp".parse::<Model>().is_err());
+    }
+}
@@ -0,0 +1,125 @@
+use std::ops::Range;
+
+pub mod model;
+pub mod races;
+
+pub use model::{solve, BoatModel, Linear, Solution};
+pub use races::{Mode, Races};
+
+/// A race lasting `time` milliseconds whose best distance so far is `record`
+/// millimeters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Race {
+    pub time: u64,
+    pub record: u64,
+}
+
+impl Race {
+    pub fn new(time: u64, record: u64) -> Self {
+        Race { time, record }
+    }
+
+    /// How far the boat goes when the button is held for `hold` milliseconds.
+    pub fn distance(&self, hold: u64) -> u128 {
+        Linear.distance(self.time, hold)
+    }
+
+    /// Whether holding for `hold` milliseconds beats the record. Matching it is
+    /// not enough.
+    pub fn wins(&self, hold: u64) -> bool {
+        self.distance(hold) > self.record as u128
+    }
+
+    /// Every hold time that beats the record, empty if none does.
+    ///
+    /// `hold * (time - hold) > record` holds strictly between the roots of
+    /// `hold² - time·hold + record`, `(time ± √(time² - 4·record)) / 2`. The
+    /// integer square root lands within a step of the first winning hold, and
+    /// the distance is symmetric around `time / 2`, so the last winning hold
+    /// mirrors the first.
+    pub fn winning_holds(&self) -> Range<u64> {
+        let middle = self.time / 2;
+        if !self.wins(middle) {
+            return middle..middle;
+        }
+        let time = self.time as u128;
+        // Positive, because the middle hold wins.
+        let discriminant = time * time - 4 * self.record as u128;
+        let mut first = ((time - discriminant.isqrt()) / 2) as u64;
+        while first > 0 && self.wins(first - 1) {
+            first -= 1;
+        }
+        while !self.wins(first) {
+            first += 1;
+        }
+        first..self.time - first + 1
+    }
+
+    /// How many hold times beat the record.
+    pub fn margin(&self) -> u64 {
+        let holds = self.winning_holds();
+        holds.end - holds.start
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    #[allow(unused_imports)]
+    use super::*;
+
+    #[test]
+    fn test_example() {
+        let races = [Race::new(7, 9), Race::new(15, 40), Race::new(30, 200)];
+        let holds: Vec<Range<u64>> = races.iter().map(Race::winning_holds).collect();
+        assert_eq!(holds, vec![2..6, 4..12, 11..20]);
+        assert_eq!(races.iter().map(Race::margin).product::<u64>(), 288);
+        assert_eq!(Race::new(71530, 940200).margin(), 71503);
+    }
+
+    #[test]
+    fn test_ties() {
+        // Holding 10 of 30 ties a record of 200 without beating it.
+        assert_eq!(Race::new(30, 200).winning_holds(), 11..20);
+        assert_eq!(Race::new(30, 199).winning_holds(), 10..21);
+        // The best hold only ties.
+        assert_eq!(Race::new(30, 225).margin(), 0);
+        assert_eq!(Race::new(30, 224).winning_holds(), 15..16);
+        assert_eq!(Race::new(0, 0).margin(), 0);
+        assert_eq!(Race::new(1, 0).margin(), 0);
+        assert_eq!(Race::new(2, 0).winning_holds(), 1..2);
+    }
+
+    #[test]
+    fn test_large() {
+        // Far past what f64 represents exactly. Holding 1 goes `time - 1` and
+        // holding 2 goes nearly twice as far.
+        let time = u64::MAX - 1;
+        assert_eq!(Race::new(time, u64::MAX).winning_holds(), 2..time - 1);
+        assert_eq!(Race::new(time, time - 1).winning_holds(), 2..time - 1);
+        assert_eq!(Race::new(time, time - 2).winning_holds(), 1..time);
+    }
+
+    fn brute_force(race: &Race) -> Vec<u64> {
+        (0..=race.time).filter(|hold| race.wins(*hold)).collect()
+    }
+
+    proptest! {
+        #[test]
+        fn test_matches_brute_force(time in 0..500u64, record in 0..70_000u64) {
+            let race = Race::new(time, record);
+            let holds: Vec<u64> = race.winning_holds().collect();
+            prop_assert_eq!(holds, brute_force(&race));
+        }
+
+        #[test]
+        fn test_tie_boundaries(time in 0..500u64, hold in 0..500u64, slack in 0..2u64) {
+            // Records equal to, or just below, some reachable distance.
+            let record = (Race::new(time, 0).distance(hold) as u64).saturating_sub(slack);
+            let race = Race::new(time, record);
+            let holds: Vec<u64> = race.winning_holds().collect();
+            prop_assert_eq!(holds, brute_force(&race));
+        }
+    }
+}
@@ -0,0 +1,301 @@
+//! How far a boat goes for each hold time, beyond the puzzle's linear charge.
+//!
+//! Every model must have distances that rise strictly up to a peak hold time
+//! and never rise after it, so that the holds beating any record form a single
+//! range around the peak. The solver then finds that range with binary
+//! searches, unless the model can work it out directly.
+use std::fmt;
+use std::ops::Range;
+
+use crate::Race;
+
+pub trait BoatModel {
+    /// How far the boat goes in a race lasting `time` milliseconds when the
+    /// button is held for `hold` of them.
+    fn distance(&self, time: u64, hold: u64) -> u128;
+
+    /// The hold time with the longest distance, the first one if several tie.
+    fn peak(&self, time: u64) -> u64 {
+        // The first hold that does no better than the one before it.
+        let (mut lo, mut hi) = (0, time);
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            if self.distance(time, mid + 1) <= self.distance(time, mid) {
+                hi = mid;
+            } else {
+                lo = mid + 1;
+            }
+        }
+        lo
+    }
+
+    /// Every hold time that beats the record of `race`, for models that can
+    /// solve for it without searching.
+    fn winning_holds(&self, _race: &Race) -> Option<Range<u64>> {
+        None
+    }
+}
+
+/// The puzzle's boats: each millisecond held adds 1 mm/ms of speed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Linear;
+
+impl BoatModel for Linear {
+    fn distance(&self, time: u64, hold: u64) -> u128 {
+        if hold >= time {
+            return 0;
+        }
+        hold as u128 * (time - hold) as u128
+    }
+
+    fn peak(&self, time: u64) -> u64 {
+        time / 2
+    }
+
+    fn winning_holds(&self, race: &Race) -> Option<Range<u64>> {
+        Some(race.winning_holds())
+    }
+}
+
+/// Linear charge that stops adding speed at `cap` mm/ms.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpeedCap {
+    pub cap: u64,
+}
+
+impl BoatModel for SpeedCap {
+    fn distance(&self, time: u64, hold: u64) -> u128 {
+        if hold >= time {
+            return 0;
+        }
+        hold.min(self.cap) as u128 * (time - hold) as u128
+    }
+
+    fn peak(&self, time: u64) -> u64 {
+        self.cap.min(time / 2)
+    }
+
+    /// Up to the cap the boat is linear, and past it the distance is
+    /// `cap * (time - hold)`, which beats `record` while
+    /// `hold < time - record / cap`.
+    fn winning_holds(&self, race: &Race) -> Option<Range<u64>> {
+        let peak = self.peak(race.time);
+        if self.distance(race.time, peak) <= race.record as u128 {
+            return Some(peak..peak);
+        }
+        let linear = race.winning_holds();
+        let end = if linear.end < self.cap {
+            linear.end
+        } else {
+            (race.time - race.record / self.cap).max(self.cap)
+        };
+        Some(linear.start..end)
+    }
+}
+
+/// Linear charge, but once released the boat loses `drag` mm/ms of speed every
+/// millisecond after the first until it stops.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Drag {
+    pub drag: u64,
+}
+
+impl BoatModel for Drag {
+    fn distance(&self, time: u64, hold: u64) -> u128 {
+        if hold >= time {
+            return 0;
+        }
+        let (speed, drag) = (hold as u128, self.drag as u128);
+        let remaining = (time - hold) as u128;
+        if drag == 0 {
+            return speed * remaining;
+        }
+        // Milliseconds spent moving, at speeds `speed`, `speed - drag`, ...
+        let moving = remaining.min(speed.div_ceil(drag));
+        if moving == 0 {
+            return 0;
+        }
+        moving * speed - drag * (moving - 1) * moving / 2
+    }
+}
+
+/// Speed charged in stages: each `(duration, rate)` segment adds `rate` mm/ms
+/// for each of its `duration` milliseconds held, in order, and holding past
+/// the last segment adds nothing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Piecewise {
+    segments: Vec<(u64, u64)>,
+}
+
+impl Piecewise {
+    /// Rates must not increase from one segment to the next, or the distance
+    /// could rise again after falling.
+    pub fn new(segments: Vec<(u64, u64)>) -> anyhow::Result<Self> {
+        if let Some(pair) = segments.windows(2).find(|pair| pair[1].1 > pair[0].1) {
+            anyhow::bail!("Rate {} follows the lower rate {}", pair[1].1, pair[0].1);
+        }
+        Ok(Piecewise { segments })
+    }
+
+    /// The speed after holding for `hold` milliseconds.
+    pub fn speed(&self, hold: u64) -> u128 {
+        let mut left = hold;
+        let mut speed = 0u128;
+        for (duration, rate) in &self.segments {
+            let held = left.min(*duration);
+            speed += held as u128 * *rate as u128;
+            left -= held;
+        }
+        speed
+    }
+}
+
+impl BoatModel for Piecewise {
+    fn distance(&self, time: u64, hold: u64) -> u128 {
+        if hold >= time {
+            return 0;
+        }
+        self.speed(hold).saturating_mul((time - hold) as u128)
+    }
+}
+
+/// The best a model can do in a race, and which holds beat the record.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Solution {
+    pub best_hold: u64,
+    pub best_distance: u128,
+    pub winning_holds: Range<u64>,
+}
+
+impl Solution {
+    pub fn margin(&self) -> u64 {
+        self.winning_holds.end - self.winning_holds.start
+    }
+}
+
+impl fmt::Display for Solution {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "best hold {} for {} mm, ", self.best_hold, self.best_distance)?;
+        match self.margin() {
+            0 => write!(f, "no winning holds"),
+            n => write!(f, "winning holds {:?} ({n})", self.winning_holds),
+        }
+    }
+}
+
+/// Find the best hold and every winning hold for `race` under `model`.
+pub fn solve<M: BoatModel + ?Sized>(model: &M, race: &Race) -> Solution {
+    let best_hold = model.peak(race.time);
+    let best_distance = model.distance(race.time, best_hold);
+    let winning_holds = model.winning_holds(race).unwrap_or_else(|| {
+        let wins = |hold| model.distance(race.time, hold) > race.record as u128;
+        if !wins(best_hold) {
+            return best_hold..best_hold;
+        }
+        // Distances rise up to the peak and fall after it.
+        let start = first(0, best_hold, wins);
+        let end = first(best_hold, race.time, |hold| !wins(hold));
+        start..end
+    });
+    Solution { best_hold, best_distance, winning_holds }
+}
+
+/// The first value in `lo..hi` for which `pred` holds, or `hi`, for a `pred`
+/// that is false and then true.
+fn first(mut lo: u64, mut hi: u64, pred: impl Fn(u64) -> bool) -> u64 {
+    while lo < hi {
+        let mid = lo + (hi - lo) / 2;
+        if pred(mid) {
+            hi = mid;
+        } else {
+            lo = mid + 1;
+        }
+    }
+    lo
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    #[allow(unused_imports)]
+    use super::*;
+
+    #[test]
+    fn test_linear() {
+        let solution = solve(&Linear, &Race::new(30, 200));
+        assert_eq!(solution.best_hold, 15);
+        assert_eq!(solution.best_distance, 225);
+        assert_eq!(solution.winning_holds, 11..20);
+        assert_eq!(solution.to_string(), "best hold 15 for 225 mm, winning holds 11..20 (9)");
+    }
+
+    #[test]
+    fn test_models() {
+        let race = Race::new(30, 200);
+        assert_eq!(solve(&SpeedCap { cap: 10 }, &race).winning_holds, 10..10);
+        let capped = solve(&SpeedCap { cap: 12 }, &race);
+        assert_eq!((capped.best_hold, capped.best_distance), (12, 216));
+        assert_eq!(capped.winning_holds, 11..14);
+        let drag = solve(&Drag { drag: 1 }, &race);
+        assert_eq!((drag.best_hold, drag.best_distance), (20, 155));
+        assert_eq!(drag.margin(), 0);
+        assert_eq!(solve(&Drag { drag: 1 }, &Race::new(30, 150)).winning_holds, 19..22);
+        // Quick to charge, then slow.
+        let staged = Piecewise::new(vec![(5, 4), (10, 1)]).unwrap();
+        assert_eq!(staged.speed(8), 23);
+        let staged = solve(&staged, &race);
+        assert_eq!((staged.best_hold, staged.best_distance), (7, 506));
+        assert_eq!(staged.winning_holds, 2..24);
+        assert!(Piecewise::new(vec![(5, 1), (5, 2)]).is_err());
+    }
+
+    /// Parameters for one of the models, so proptest can show a failing case.
+    #[derive(Debug, Clone)]
+    enum Params {
+        Linear,
+        SpeedCap(u64),
+        Drag(u64),
+        Piecewise(Vec<(u64, u64)>),
+    }
+
+    impl Params {
+        fn model(&self) -> Box<dyn BoatModel> {
+            match self {
+                Params::Linear => Box::new(Linear),
+                Params::SpeedCap(cap) => Box::new(SpeedCap { cap: *cap }),
+                Params::Drag(drag) => Box::new(Drag { drag: *drag }),
+                Params::Piecewise(segments) => Box::new(Piecewise::new(segments.clone()).unwrap()),
+            }
+        }
+    }
+
+    fn params() -> impl Strategy<Value = Params> {
+        prop_oneof![
+            Just(Params::Linear),
+            (0..60u64).prop_map(Params::SpeedCap),
+            (0..8u64).prop_map(Params::Drag),
+            prop::collection::vec((0..30u64, 0..6u64), 0..4).prop_map(|mut segments| {
+                segments.sort_by_key(|(_, rate)| std::cmp::Reverse(*rate));
+                Params::Piecewise(segments)
+            }),
+        ]
+    }
+
+    proptest! {
+        #[test]
+        fn test_matches_brute_force(params in params(), time in 0..150u64, record in 0..6000u64) {
+            let model = params.model();
+            let race = Race::new(time, record);
+            let distances: Vec<u128> = (0..=time).map(|hold| model.distance(time, hold)).collect();
+            let winning: Vec<u64> =
+                (0..=time).filter(|hold| distances[*hold as usize] > record as u128).collect();
+            let best = *distances.iter().max().unwrap();
+            let solution = solve(model.as_ref(), &race);
+            prop_assert_eq!(solution.best_distance, best);
+            let best_hold = distances.iter().position(|d| *d == best).unwrap() as u64;
+            prop_assert_eq!(solution.best_hold, best_hold);
+            prop_assert_eq!(solution.winning_holds.collect::<Vec<_>>(), winning);
+        }
+    }
+}
@@ -0,0 +1,181 @@
+//! The race sheet: a `Time:` row and a `Distance:` row of numbers.
+use num_bigint::BigUint;
+
+use crate::Race;
+
+/// How the numbers on each row are read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    /// One race per column, as in part 1.
+    Spaced,
+    /// The spaces are bad kerning, and each row is a single number, as in part 2.
+    Kerned,
+}
+
+/// Times and record distances, in any size until converted to [`Race`]s.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Races {
+    pub times: Vec<BigUint>,
+    pub records: Vec<BigUint>,
+}
+
+impl Races {
+    pub fn parse(s: &str, mode: Mode) -> anyhow::Result<Races> {
+        let mut times: Option<(usize, Vec<BigUint>)> = None;
+        let mut records: Option<(usize, Vec<BigUint>)> = None;
+        for (i, line) in s.lines().enumerate() {
+            let number = i + 1;
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let (label, rest) = line.split_once(':').ok_or_else(|| {
+                anyhow::anyhow!("Line {number}: expected `Time:` or `Distance:`, found {line:?}")
+            })?;
+            let row = match label {
+                "Time" => &mut times,
+                "Distance" => &mut records,
+                _ => anyhow::bail!("Line {number}: unknown row {label:?}"),
+            };
+            if let Some((first, _)) = row {
+                anyhow::bail!("Line {number}: duplicate {label} row, first seen on line {first}");
+            }
+            let values = Races::parse_row(rest, mode)
+                .map_err(|err| anyhow::anyhow!("Line {number}: {err}"))?;
+            *row = Some((number, values));
+        }
+        let (_, times) = times.ok_or_else(|| anyhow::anyhow!("Missing `Time:` row"))?;
+        let (_, records) = records.ok_or_else(|| anyhow::anyhow!("Missing `Distance:` row"))?;
+        if times.len() != records.len() {
+            anyhow::bail!("Found {} times but {} distances", times.len(), records.len());
+        }
+        Ok(Races { times, records })
+    }
+
+    fn parse_row(row: &str, mode: Mode) -> anyhow::Result<Vec<BigUint>> {
+        let words: Vec<&str> = row.split_whitespace().collect();
+        if words.is_empty() {
+            anyhow::bail!("no numbers");
+        }
+        if let Some(word) = words.iter().find(|w| !w.bytes().all(|b| b.is_ascii_digit())) {
+            anyhow::bail!("expected a number, found {word:?}");
+        }
+        let values = match mode {
+            Mode::Spaced => words.iter().map(|w| w.parse()).collect::<Result<_, _>>()?,
+            Mode::Kerned => vec![words.concat().parse()?],
+        };
+        Ok(values)
+    }
+
+    pub fn len(&self) -> usize {
+        self.times.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.times.is_empty()
+    }
+
+    /// The races, failing on any number too large for the solver.
+    pub fn races(&self) -> anyhow::Result<Vec<Race>> {
+        let fit = |value: &BigUint, what: &str, race: usize| {
+            u64::try_from(value)
+                .map_err(|_| anyhow::anyhow!("Race {race}: {what} {value} does not fit in 64 bits"))
+        };
+        self.times
+            .iter()
+            .zip(&self.records)
+            .enumerate()
+            .map(|(i, (time, record))| {
+                Ok(Race::new(fit(time, "time", i + 1)?, fit(record, "distance", i + 1)?))
+            })
+            .collect()
+    }
+
+    /// How many hold times beat the record in each race, however large the
+    /// numbers.
+    pub fn margins(&self) -> Vec<BigUint> {
+        self.times.iter().zip(&self.records).map(|(time, record)| margin(time, record)).collect()
+    }
+}
+
+/// [`Race::margin`] in arbitrary precision, by the same root bracketing as
+/// [`Race::winning_holds`].
+fn margin(time: &BigUint, record: &BigUint) -> BigUint {
+    let one = BigUint::from(1u32);
+    let wins = |hold: &BigUint| hold * (time - hold) > *record;
+    if !wins(&(time / 2u32)) {
+        return BigUint::default();
+    }
+    // Positive, because the middle hold wins.
+    let discriminant = time * time - record * 4u32;
+    let mut first = (time - discriminant.sqrt()) / 2u32;
+    while first > BigUint::default() && wins(&(&first - &one)) {
+        first -= &one;
+    }
+    while !wins(&first) {
+        first += &one;
+    }
+    time + one - first * 2u32
+}
+
+#[cfg(test)]
+mod tests {
+    #[allow(unused_imports)]
+    use super::*;
+
+    const EXAMPLE: &str = include_str!("../example.txt");
+
+    #[test]
+    fn test_modes() {
+        let spaced = Races::parse(EXAMPLE, Mode::Spaced).unwrap().races().unwrap();
+        assert_eq!(spaced, vec![Race::new(7, 9), Race::new(15, 40), Race::new(30, 200)]);
+        let kerned = Races::parse(EXAMPLE, Mode::Kerned).unwrap().races().unwrap();
+        assert_eq!(kerned, vec![Race::new(71530, 940200)]);
+    }
+
+    #[test]
+    fn test_errors() {
+        let error = |s: &str, mode| Races::parse(s, mode).unwrap_err().to_string();
+        assert_eq!(error("Time: 7 15\n", Mode::Spaced), "Missing `Distance:` row");
+        assert_eq!(error("Distance: 9\n", Mode::Kerned), "Missing `Time:` row");
+        assert_eq!(error("Time: 7 15\nDistance: 9\n", Mode::Spaced), "Found 2 times but 1 distances");
+        assert_eq!(
+            error("Time: 7\nTime: 8\nDistance: 9\n", Mode::Spaced),
+            "Line 2: duplicate Time row, first seen on line 1",
+        );
+        assert_eq!(
+            error("Time: 7 x\nDistance: 9 1\n", Mode::Spaced),
+            "Line 1: expected a number, found \"x\"",
+        );
+        assert_eq!(error("Time: 7\nDistance:\n", Mode::Kerned), "Line 2: no numbers");
+        assert_eq!(error("Speed: 7\n", Mode::Spaced), "Line 1: unknown row \"Speed\"");
+        // Kerning doesn't change how many races there are.
+        assert!(Races::parse("Time: 7 15\nDistance: 9\n", Mode::Kerned).is_ok());
+    }
+
+    #[test]
+    fn test_big_numbers() {
+        let input = "Time: 1844674407 3709551616\nDistance: 1 2\n";
+        let races = Races::parse(input, Mode::Kerned).unwrap();
+        assert_eq!(races.times[0].to_string(), "18446744073709551616");
+        assert_eq!(
+            races.races().unwrap_err().to_string(),
+            "Race 1: time 18446744073709551616 does not fit in 64 bits",
+        );
+        let input = "Time: 1844674407370 9563961\nDistance: 1329227995784915 872903807060280344576\n";
+        let races = Races::parse(input, Mode::Kerned).unwrap();
+        assert_eq!(races.margins()[0].to_string(), "18302061494208767286");
+    }
+
+    #[test]
+    fn test_margins() {
+        for mode in [Mode::Spaced, Mode::Kerned] {
+            let races = Races::parse(EXAMPLE, mode).unwrap();
+            let expected: Vec<BigUint> =
+                races.races().unwrap().iter().map(|race| race.margin().into()).collect();
+            assert_eq!(races.margins(), expected);
+        }
+        let races = Races::parse("Time: 30 30 0 2\nDistance: 225 224 0 0\n", Mode::Spaced).unwrap();
+        assert_eq!(races.margins(), vec![0u32.into(), 1u32.into(), 0u32.into(), 1u32.into()]);
+    }
+}
@@ -0,0 +1,323 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::str::FromStr;
+
+pub const VALID_CARDS: [char; 13] = [
+    'A', '2', '3', '4', '5', '6', '7', '8', '9', 'T', 'J', 'Q', 'K',
+];
+
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone, PartialOrd, Ord)]
+pub struct Card(pub char);
+
+impl TryFrom<char> for Card {
+    type Error = anyhow::Error;
+
+    fn try_from(c: char) -> Result<Self, Self::Error> {
+        if !VALID_CARDS.contains(&c) {
+            Err(anyhow::anyhow!("Invalid card: {}", c))
+        } else {
+            Ok(Card(c))
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hand {
+    pub cards: Vec<Card>,
+    pub bid: u32,
+}
+
+/// Hand types from weakest to strongest.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+pub enum HandType {
+    HighCard,
+    OnePair,
+    TwoPair,
+    ThreeOfAKind,
+    FullHouse,
+    FourOfAKind,
+    FiveOfAKind,
+}
+
+impl HandType {
+    /// The type of a hand whose matching cards form groups of these sizes,
+    /// largest first. Hands longer than five cards count five or more of a
+    /// kind as five of a kind.
+    pub fn from_groups(groups: &[usize]) -> HandType {
+        match groups {
+            [a, ..] if *a >= 5 => HandType::FiveOfAKind,
+            [4, ..] => HandType::FourOfAKind,
+            [3, b, ..] if *b >= 2 => HandType::FullHouse,
+            [3, ..] => HandType::ThreeOfAKind,
+            [2, 2, ..] => HandType::TwoPair,
+            [2, ..] => HandType::OnePair,
+            _ => HandType::HighCard,
+        }
+    }
+}
+
+impl Hand {
+    /// The type of the hand with every card standing for itself.
+    pub fn hand_type(&self) -> HandType {
+        self.wild_hand_type(&[])
+    }
+
+    /// The best type the hand can make when every card in `wild` may stand in
+    /// for any other. Adding all of them to the largest group of other cards
+    /// is never worse than spreading them out.
+    pub fn wild_hand_type(&self, wild: &[Card]) -> HandType {
+        let mut counts = self.counts();
+        let wilds: usize = counts
+            .iter()
+            .filter(|(_, card)| wild.contains(card))
+            .map(|(count, _)| *count)
+            .sum();
+        counts.retain(|(_, card)| !wild.contains(card));
+        let mut groups: Vec<usize> = counts.iter().rev().map(|(count, _)| *count).collect();
+        match groups.first_mut() {
+            Some(largest) => *largest += wilds,
+            None => groups.push(wilds),
+        }
+        HandType::from_groups(&groups)
+    }
+
+    fn counts(&self) -> Vec<(usize, Card)> {
+        let mut counts = HashMap::new();
+        for &count in self.cards.iter() {
+            *counts.entry(count).or_insert(0) += 1;
+        }
+        let mut counts_list: Vec<(usize, Card)> = counts
+            .into_iter()
+            .map(|(card, count)| (count, card))
+            .collect();
+        counts_list.sort();
+        counts_list
+    }
+}
+
+impl FromStr for Hand {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_whitespace().collect::<Vec<&str>>().as_slice() {
+            [cards, bid] => Ok(Hand {
+                cards: cards
+                    .chars()
+                    .map(Card::try_from)
+                    .collect::<Result<Vec<Card>, _>>()?,
+                bid: bid.parse()?,
+            }),
+            _ => Err(anyhow::anyhow!("Invalid hand: {}", s)),
+        }
+    }
+}
+
+impl std::fmt::Display for Hand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let cards_str: String = self.cards.iter().map(|c| c.0).collect();
+        write!(f, "{} {}", cards_str, self.bid)
+    }
+}
+
+/// How hands of the same type are ordered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TieBreak {
+    /// Compare the first cards dealt, then the second, and so on.
+    InOrder,
+    /// Compare the strongest card of each hand, then the next strongest, and
+    /// so on, ignoring the order they were dealt in.
+    Strongest,
+}
+
+/// A variant of Camel Cards.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rules {
+    /// Every card from weakest to strongest.
+    order: Vec<Card>,
+    /// Cards that stand in for whichever card makes the best hand type. They
+    /// keep their own strength for tie breaks.
+    wild: Vec<Card>,
+    tie_break: TieBreak,
+}
+
+impl Rules {
+    /// `order` lists every card once, from weakest to strongest, and `wild`
+    /// lists the wild cards.
+    pub fn new(order: &str, wild: &str, tie_break: TieBreak) -> anyhow::Result<Rules> {
+        let order: Vec<Card> = order.chars().map(Card::try_from).collect::<Result<_, _>>()?;
+        let wild: Vec<Card> = wild.chars().map(Card::try_from).collect::<Result<_, _>>()?;
+        for c in VALID_CARDS {
+            match order.iter().filter(|card| card.0 == c).count() {
+                1 => {}
+                0 => anyhow::bail!("Card {c} is missing from the order"),
+                _ => anyhow::bail!("Card {c} appears more than once in the order"),
+            }
+        }
+        Ok(Rules { order, wild, tie_break })
+    }
+
+    /// Part 1: no wild cards and `2` up to `A`.
+    pub fn standard() -> Rules {
+        Rules::new("23456789TJQKA", "", TieBreak::InOrder).unwrap()
+    }
+
+    /// Part 2: jokers are wild, and the weakest card on their own.
+    pub fn jokers() -> Rules {
+        Rules::new("J23456789TQKA", "J", TieBreak::InOrder).unwrap()
+    }
+
+    /// The position of `card` in the order, higher is stronger.
+    pub fn strength(&self, card: Card) -> usize {
+        self.order.iter().position(|c| *c == card).unwrap()
+    }
+
+    pub fn hand_type(&self, hand: &Hand) -> HandType {
+        hand.wild_hand_type(&self.wild)
+    }
+
+    /// Sorting by this key sorts hands from weakest to strongest.
+    pub fn key(&self, hand: &Hand) -> (HandType, Vec<usize>) {
+        let mut strengths: Vec<usize> =
+            hand.cards.iter().map(|card| self.strength(*card)).collect();
+        if self.tie_break == TieBreak::Strongest {
+            strengths.sort_by(|a, b| b.cmp(a));
+        }
+        (self.hand_type(hand), strengths)
+    }
+
+    pub fn compare(&self, a: &Hand, b: &Hand) -> Ordering {
+        self.key(a).cmp(&self.key(b))
+    }
+}
+
+/// Total winnings: each bid times the rank of its hand, weakest first.
+pub fn play(hands: &[Hand], rules: &Rules) -> usize {
+    let mut hands: Vec<&Hand> = hands.iter().collect();
+    hands.sort_by_cached_key(|hand| rules.key(hand));
+    hands
+        .into_iter()
+        .zip(1..)
+        .map(|(hand, rank)| rank * hand.bid as usize)
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    #[allow(unused_imports)]
+    use super::*;
+
+    const EXAMPLE: &str = include_str!("../example.txt");
+
+    fn hands(s: &str) -> Vec<Hand> {
+        s.lines().map(|line| Hand::from_str(line).unwrap()).collect()
+    }
+
+    #[test]
+    fn test_part1_example() {
+        assert_eq!(play(&hands(EXAMPLE), &Rules::standard()), 6440);
+    }
+
+    #[test]
+    fn test_part2_example() {
+        assert_eq!(play(&hands(EXAMPLE), &Rules::jokers()), 5905);
+    }
+
+    #[test]
+    fn test_card_scores() {
+        let rules = Rules::standard();
+        let hands = hands("KQJT9 0\nKTJJT 0\nKK877 0\nT55J5 0\nT55K5 0\n45555 0\n53333 0");
+        for (low_hand, high_hand) in hands.iter().zip(hands.iter().skip(1)) {
+            assert_eq!(rules.compare(low_hand, high_hand), Ordering::Less, "{low_hand}");
+        }
+    }
+
+    #[test]
+    fn test_five_card_types() {
+        let types: Vec<HandType> = ["23456", "22345", "22334", "22234", "22233", "22223", "22222"]
+            .iter()
+            .map(|cards| Hand::from_str(&format!("{cards} 0")).unwrap().hand_type())
+            .collect();
+        let mut sorted = types.clone();
+        sorted.sort();
+        sorted.dedup();
+        assert_eq!(types, sorted);
+        assert_eq!(types.len(), 7);
+    }
+
+    /// The best type over every way of replacing each wild card with a card
+    /// from `deck`.
+    fn brute_force(cards: &mut Vec<Card>, from: usize, wild: &[Card], deck: &[Card]) -> HandType {
+        let Some(i) = (from..cards.len()).find(|i| wild.contains(&cards[*i])) else {
+            return Hand { cards: cards.clone(), bid: 0 }.hand_type();
+        };
+        let original = cards[i];
+        let best = deck
+            .iter()
+            .map(|card| {
+                cards[i] = *card;
+                brute_force(cards, i + 1, wild, deck)
+            })
+            .max()
+            .unwrap();
+        cards[i] = original;
+        best
+    }
+
+    #[test]
+    fn test_wild_matches_brute_force() {
+        // Five ranks are enough for hands of up to five cards to make every
+        // grouping, with one or two of the ranks wild.
+        let deck: Vec<Card> = "234JQ".chars().map(Card).collect();
+        for wild in ["", "J", "JQ"] {
+            let wild: Vec<Card> = wild.chars().map(Card).collect();
+            for len in 0..=5u32 {
+                for n in 0..deck.len().pow(len) {
+                    let mut cards: Vec<Card> = (0..len)
+                        .scan(n, |rest, _| {
+                            let card = deck[*rest % deck.len()];
+                            *rest /= deck.len();
+                            Some(card)
+                        })
+                        .collect();
+                    let hand = Hand { cards: cards.clone(), bid: 0 };
+                    assert_eq!(
+                        hand.wild_hand_type(&wild),
+                        brute_force(&mut cards, 0, &wild, &deck),
+                        "{hand} with {wild:?} wild",
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_long_hands() {
+        let jokers = Rules::jokers();
+        let hand = |cards: &str| Hand::from_str(&format!("{cards} 0")).unwrap();
+        assert_eq!(jokers.hand_type(&hand("2233344")), HandType::FullHouse);
+        assert_eq!(jokers.hand_type(&hand("22J3456")), HandType::ThreeOfAKind);
+        assert_eq!(jokers.hand_type(&hand("2JJJJJJ")), HandType::FiveOfAKind);
+        assert_eq!(jokers.hand_type(&hand("JJ")), HandType::OnePair);
+        assert_eq!(Rules::standard().hand_type(&hand("A")), HandType::HighCard);
+    }
+
+    #[test]
+    fn test_rules() {
+        let jokers = Rules::jokers();
+        let hand = Hand::from_str("KTJJT 0").unwrap();
+        assert_eq!(Rules::standard().hand_type(&hand), HandType::TwoPair);
+        assert_eq!(jokers.hand_type(&hand), HandType::FourOfAKind);
+        assert_eq!(jokers.strength(Card('J')), 0);
+        // Deuces wild, with hands of a type ordered by their best cards.
+        let deuces = Rules::new("23456789TJQKA", "2", TieBreak::Strongest).unwrap();
+        let (low, high) = (Hand::from_str("A2345 0").unwrap(), Hand::from_str("3K224 0").unwrap());
+        assert_eq!(deuces.hand_type(&low), HandType::OnePair);
+        assert_eq!(deuces.hand_type(&high), HandType::ThreeOfAKind);
+        let (low, high) = (Hand::from_str("KQ345 0").unwrap(), Hand::from_str("3456A 0").unwrap());
+        assert_eq!(deuces.compare(&low, &high), Ordering::Less);
+        assert_eq!(Rules::standard().compare(&low, &high), Ordering::Greater);
+        assert!(Rules::new("23456789TJQK", "", TieBreak::InOrder).is_err());
+        assert!(Rules::new("223456789TJQKA", "", TieBreak::InOrder).is_err());
+    }
+}
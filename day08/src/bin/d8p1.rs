@@ -4,7 +4,6 @@ use std::fs::File;
 use std::io::prelude::*;
 use std::io::BufReader;
 use std::str::FromStr;
-use std::collections::HashSet;
 use num::integer::lcm;
 
 lazy_static! {
@@ -42,6 +41,7 @@ enum Direction {
 #[derive(Debug, Clone)]
 struct Map {
     nodes: HashMap<String, Node>,
+    #[allow(dead_code)]
     first_node: String,
     directions: Vec<Direction>,
 }
@@ -111,7 +111,7 @@ fn part2(map: &Map) -> anyhow::Result<usize> {
         .map(|s| s.as_str())
         .collect();
     //let mut circuit_breaker = HashSet::new();
-    let mut first_z: Vec<usize> = std::iter::repeat(0).take(ptrs.len()).collect();
+    let mut first_z: Vec<usize> = std::iter::repeat_n(0, ptrs.len()).collect();
     for step in 0..1000000000 {
         //println!("Step: {} Paths: {:?}", step, ptrs);
         for (i, ptr) in ptrs.iter().enumerate() {
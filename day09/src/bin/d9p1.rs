@@ -18,7 +18,7 @@ impl FromStr for Reading {
 }
 
 impl Reading {
-    fn delta(history: &Vec<i32>) -> Vec<i32> {
+    fn delta(history: &[i32]) -> Vec<i32> {
         let mut delta = Vec::with_capacity(history.len() - 1);
         for i in 1..history.len() {
             delta.push(history[i] - history[i - 1]);
@@ -18,7 +18,7 @@ impl FromStr for Reading {
 }
 
 impl Reading {
-    fn delta(history: &Vec<i32>) -> Vec<i32> {
+    fn delta(history: &[i32]) -> Vec<i32> {
         let mut delta = Vec::with_capacity(history.len() - 1);
         for i in 1..history.len() {
             delta.push(history[i] - history[i - 1]);
@@ -26,6 +26,7 @@ impl Reading {
         delta
     }
 
+    #[allow(dead_code)]
     fn extrapolate(&self) -> i32 {
         let mut deltas: Vec<Vec<i32>> = vec![self.0.clone()];
         for depth in 0.. {
@@ -1,86 +1,29 @@
-use std::hash::Hash;
 use std::str::FromStr;
 
-#[derive(Clone, Copy, PartialEq, Eq, Hash)]
-pub struct Point {
-    pub x: usize,
-    pub y: usize,
-}
-
-impl std::fmt::Debug for Point {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "Point({},{})", self.x, self.y)
-    }
-}
-
-impl From<(usize, usize)> for Point {
-    fn from((x, y): (usize, usize)) -> Self {
-        Point { x, y }
-    }
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum Direction {
-    N,
-    S,
-    E,
-    W,
-}
+pub use grid::{Direction, Point};
 
 #[derive(Clone)]
 pub struct Grid {
     pub start: Point,
-    grid: Vec<Vec<char>>,
+    grid: grid::Grid<char>,
 }
 
 impl FromStr for Grid {
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut grid = Vec::new();
+        let trimmed: Vec<&str> = s.lines().map(str::trim).collect();
         let mut start = Point { x: 0, y: 0 };
-        for (lineno, line) in s.lines().enumerate() {
-            if line.trim().is_empty() {
-                continue;
+        let grid = grid::Grid::parse(&trimmed.join("\n"), |point, c| {
+            if c == 'S' {
+                start = point;
             }
-            let mut row = Vec::new();
-            for (colno, c) in line.trim().chars().enumerate() {
-                if c == 'S' {
-                    start = Point {
-                        x: colno,
-                        y: lineno,
-                    };
-                }
-                row.push(c);
-            }
-            grid.push(row);
-        }
+            Ok(c)
+        })?;
         Ok(Grid { start, grid })
     }
 }
 
-struct GridIterator<'a> {
-    grid: &'a Grid,
-    current: Point,
-}
-
-impl<'a> Iterator for GridIterator<'a> {
-    type Item = Point;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        if self.grid.width() > self.current.x + 1 {
-            self.current.x += 1;
-            return Some(self.current);
-        }
-        if self.grid.height() > self.current.y + 1 {
-            self.current.x = 0;
-            self.current.y += 1;
-            return Some(self.current);
-        }
-        None
-    }
-}
-
 struct PathIterator<'a> {
     grid: &'a Grid,
     current: Point,
@@ -174,23 +117,23 @@ impl<'a> Iterator for PathIterator<'a> {
 
 impl Grid {
     pub fn width(&self) -> usize {
-        self.grid[0].len()
+        self.grid.width()
     }
 
     pub fn height(&self) -> usize {
-        self.grid.len()
+        self.grid.height()
     }
 
     pub fn size(&self) -> usize {
-        self.width() * self.height()
+        self.grid.len()
     }
 
     pub fn get(&self, x: usize, y: usize) -> Option<char> {
-        self.grid.get(y).and_then(|row| row.get(x)).copied()
+        self.grid.get(Point { x, y }).copied()
     }
 
     pub fn get_mut(&mut self, x: usize, y: usize) -> Option<&mut char> {
-        self.grid.get_mut(y).and_then(|row| row.get_mut(x))
+        self.grid.get_mut(Point { x, y })
     }
 
     pub fn rel_direction(&self, origin: Point, adjacent: Point) -> Option<Direction> {
@@ -207,25 +150,7 @@ impl Grid {
     }
 
     pub fn rel_pt(&self, origin: Point, direction: Direction) -> Option<Point> {
-        let pt = match direction {
-            Direction::N => origin.y.checked_sub(1).map(|y| Point { x: origin.x, y }),
-            Direction::S => Some(Point {
-                x: origin.x,
-                y: origin.y + 1,
-            }),
-            Direction::E => Some(Point {
-                x: origin.x + 1,
-                y: origin.y,
-            }),
-            Direction::W => origin.x.checked_sub(1).map(|x| Point { x, y: origin.y }),
-        };
-        pt.and_then(|p| {
-            if self.get(p.x, p.y).is_some() {
-                Some(p)
-            } else {
-                None
-            }
-        })
+        self.grid.step(origin, direction)
     }
 
     pub fn start_points(&self, start: Point) -> Vec<Point> {
@@ -271,10 +196,7 @@ impl Grid {
     }
 
     pub fn iter(&self) -> impl Iterator<Item = Point> + '_ {
-        GridIterator {
-            grid: self,
-            current: Point { x: 0, y: 0 },
-        }
+        self.grid.points()
     }
 
     pub fn path(&self, start: Point) -> Option<impl Iterator<Item = Point> + '_> {
@@ -290,56 +212,54 @@ impl Grid {
     }
 
     pub fn expand(&self) -> Self {
-        let width = self.width() * 3;
-        let height = self.height() * 3;
         let start = Point {
             x: self.start.x * 3 + 1,
             y: self.start.y * 3 + 1,
         };
         let mut grid = Grid {
             start,
-            grid: vec![vec!['.'; width]; height],
+            grid: grid::Grid::new(self.width() * 3, self.height() * 3, '.'),
         };
         for point in self.iter() {
             let top_left_x = point.x * 3;
             let top_left_y = point.y * 3;
             match self.get(point.x, point.y) {
                 Some('F') => {
-                    grid.grid[top_left_y + 1][top_left_x + 1] = 'F';
-                    grid.grid[top_left_y + 1][top_left_x + 2] = '-';
-                    grid.grid[top_left_y + 2][top_left_x + 1] = '|';
+                    grid.grid[Point { x: top_left_x + 1, y: top_left_y + 1 }] = 'F';
+                    grid.grid[Point { x: top_left_x + 2, y: top_left_y + 1 }] = '-';
+                    grid.grid[Point { x: top_left_x + 1, y: top_left_y + 2 }] = '|';
                 }
                 Some('7') => {
-                    grid.grid[top_left_y + 1][top_left_x + 1] = '7';
-                    grid.grid[top_left_y + 1][top_left_x] = '-';
-                    grid.grid[top_left_y + 2][top_left_x + 1] = '|';
+                    grid.grid[Point { x: top_left_x + 1, y: top_left_y + 1 }] = '7';
+                    grid.grid[Point { x: top_left_x, y: top_left_y + 1 }] = '-';
+                    grid.grid[Point { x: top_left_x + 1, y: top_left_y + 2 }] = '|';
                 }
                 Some('J') => {
-                    grid.grid[top_left_y + 1][top_left_x + 1] = 'J';
-                    grid.grid[top_left_y + 1][top_left_x] = '-';
-                    grid.grid[top_left_y][top_left_x + 1] = '|';
+                    grid.grid[Point { x: top_left_x + 1, y: top_left_y + 1 }] = 'J';
+                    grid.grid[Point { x: top_left_x, y: top_left_y + 1 }] = '-';
+                    grid.grid[Point { x: top_left_x + 1, y: top_left_y }] = '|';
                 }
                 Some('L') => {
-                    grid.grid[top_left_y + 1][top_left_x + 1] = 'L';
-                    grid.grid[top_left_y + 1][top_left_x + 2] = '-';
-                    grid.grid[top_left_y][top_left_x + 1] = '|';
+                    grid.grid[Point { x: top_left_x + 1, y: top_left_y + 1 }] = 'L';
+                    grid.grid[Point { x: top_left_x + 2, y: top_left_y + 1 }] = '-';
+                    grid.grid[Point { x: top_left_x + 1, y: top_left_y }] = '|';
                 }
                 Some('|') => {
-                    grid.grid[top_left_y][top_left_x + 1] = '|';
-                    grid.grid[top_left_y + 1][top_left_x + 1] = '|';
-                    grid.grid[top_left_y + 2][top_left_x + 1] = '|';
+                    grid.grid[Point { x: top_left_x + 1, y: top_left_y }] = '|';
+                    grid.grid[Point { x: top_left_x + 1, y: top_left_y + 1 }] = '|';
+                    grid.grid[Point { x: top_left_x + 1, y: top_left_y + 2 }] = '|';
                 }
                 Some('-') => {
-                    grid.grid[top_left_y + 1][top_left_x] = '-';
-                    grid.grid[top_left_y + 1][top_left_x + 1] = '-';
-                    grid.grid[top_left_y + 1][top_left_x + 2] = '-';
+                    grid.grid[Point { x: top_left_x, y: top_left_y + 1 }] = '-';
+                    grid.grid[Point { x: top_left_x + 1, y: top_left_y + 1 }] = '-';
+                    grid.grid[Point { x: top_left_x + 2, y: top_left_y + 1 }] = '-';
                 }
                 _ => {}
             }
         }
 
         // conditionally fill in spaces surrounding start point 
-        grid.grid[start.y][start.x] = 'S';
+        grid.grid[Point { x: start.x, y: start.y }] = 'S';
         [
             (Some(start.x), Some(start.y + 2), '|'),
             (Some(start.x), start.y.checked_sub(2), '|'),
@@ -365,7 +285,7 @@ impl Grid {
     }
 
     #[allow(private_interfaces)]
-    pub fn pp(&self) -> GridPP {
+    pub fn pp(&self) -> GridPP<'_> {
         GridPP(self)
     }
 }
@@ -374,7 +294,7 @@ struct GridPP<'a>(&'a Grid);
 
 impl<'a> std::fmt::Display for GridPP<'a> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        for (rowno, row) in self.0.grid.iter().enumerate() {
+        for (rowno, row) in self.0.grid.rows().enumerate() {
             for c in row {
                 match c {
                     'F' => write!(f, "┌")?,
@@ -387,7 +307,7 @@ impl<'a> std::fmt::Display for GridPP<'a> {
                     _ => write!(f, "{c}")?,
                 }
             }
-            if rowno < self.0.grid.len() - 1 {
+            if rowno < self.0.height() - 1 {
                 writeln!(f)?;
             }
         }
@@ -397,15 +317,7 @@ impl<'a> std::fmt::Display for GridPP<'a> {
 
 impl std::fmt::Display for Grid {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        for (rowno, row) in self.grid.iter().enumerate() {
-            for c in row {
-                write!(f, "{c}")?;
-            }
-            if rowno < self.grid.len() - 1 {
-                writeln!(f)?;
-            }
-        }
-        Ok(())
+        write!(f, "{}", self.grid)
     }
 }
 
@@ -2,7 +2,9 @@ use std::collections::BTreeMap;
 use std::collections::BTreeSet;
 use std::str::FromStr;
 
-type Point = (usize, usize);
+use grid::Grid;
+pub use grid::Point;
+
 type Index = BTreeMap<Point, usize>;
 
 #[derive(Clone)]
@@ -17,44 +19,39 @@ impl FromStr for Map {
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let grid = Grid::parse(s, |_, c| Ok(c == '#'))?;
         let mut locations = Vec::new();
         let mut index = BTreeMap::new();
-        let mut width = 0;
-        let mut height = 0;
-        for (lineno, line) in s.trim().lines().enumerate() {
-            for (colno, char) in line.chars().enumerate() {
-                if char == '#' {
-                    index.insert((colno, lineno), locations.len());
-                    locations.push((colno, lineno))
-                }
-            }
-            if line.len() > width {
-                width = line.len();
-            }
-            height += 1;
+        for (point, _) in grid.iter().filter(|(_, galaxy)| **galaxy) {
+            index.insert(point, locations.len());
+            locations.push(point);
         }
         Ok(Map {
-            height,
-            width,
+            height: grid.height(),
+            width: grid.width(),
             locations,
             index,
         })
     }
 }
 
+/// Written a row at a time from the galaxies, since an expanded map can be far
+/// too large to build as a grid.
 impl std::fmt::Display for Map {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let mut locs = self.locations.iter();
-        let mut next_loc = locs.next();
+        let mut galaxies: Vec<(usize, usize)> =
+            self.locations.iter().map(|point| (point.y, point.x)).collect();
+        galaxies.sort();
+        let mut galaxies = galaxies.into_iter().peekable();
         for y in 0..self.height {
-            for x in 0..self.width {
-                if next_loc == Some(&(x, y)) {
-                    next_loc = locs.next();
-                    write!(f, "#")?;
-                } else {
-                    write!(f, ".")?;
-                }
+            let mut x = 0;
+            while let Some((_, galaxy)) = galaxies.next_if(|(row, _)| *row == y) {
+                write!(f, "{:.<1$}#", "", galaxy - x)?;
+                x = galaxy + 1;
             }
+            writeln!(f, "{:.<1$}", "", self.width - x)?;
+        }
+        if self.height == 0 {
             writeln!(f)?;
         }
         Ok(())
@@ -63,7 +60,7 @@ impl std::fmt::Display for Map {
 
 impl Map {
     pub fn get_by_coord(&self, x: usize, y: usize) -> Option<usize> {
-        self.index.get(&(x, y)).copied()
+        self.index.get(&Point { x, y }).copied()
     }
 
     pub fn get_by_id(&self, id: usize) -> Option<Point> {
@@ -76,8 +73,8 @@ impl Map {
             .get_mut(loc_id)
             .ok_or(anyhow::anyhow!("invalid location id"))?;
         self.index.remove(curr_loc);
-        self.index.insert((x, y), loc_id);
-        *curr_loc = (x, y);
+        self.index.insert(Point { x, y }, loc_id);
+        *curr_loc = Point { x, y };
         Ok(())
     }
 
@@ -107,23 +104,23 @@ impl Map {
         let mut occupied_cols = BTreeSet::new();
         let mut occupied_rows = BTreeSet::new();
         for location in self.locations.iter() {
-            occupied_cols.insert(location.0);
-            occupied_rows.insert(location.1);
+            occupied_cols.insert(location.x);
+            occupied_rows.insert(location.y);
         }
         let occupied_cols: Vec<_> = occupied_cols.into_iter().collect();
         let occupied_rows: Vec<_> = occupied_rows.into_iter().collect();
         let mut index = BTreeMap::new();
         //let mut locations = self.locations.clone();
         for (i, loc) in self.locations.iter_mut().enumerate() {
-            let (curr_x, curr_y) = *loc;
+            let Point { x: curr_x, y: curr_y } = *loc;
             let occupied_col_before = occupied_cols.partition_point(|col| col < &curr_x);
             let occupied_row_before = occupied_rows.partition_point(|row| row < &curr_y);
             let empty_col_before = curr_x - occupied_col_before;
             let empty_row_before = curr_y - occupied_row_before;
             let new_x = empty_col_before * n + occupied_col_before;
             let new_y = empty_row_before * n + occupied_row_before;
-            *loc = (new_x, new_y);
-            index.insert((new_x, new_y), i);
+            *loc = Point { x: new_x, y: new_y };
+            index.insert(*loc, i);
         }
         self.height = (self.height - occupied_rows.len()) * n + occupied_rows.len();
         self.width = (self.width - occupied_cols.len()) * n + occupied_cols.len();
@@ -132,7 +129,7 @@ impl Map {
 }
 
 pub fn distance(a: Point, b: Point) -> usize {
-    a.0.abs_diff(b.0) + a.1.abs_diff(b.1)
+    a.manhattan(b)
 }
 
 #[cfg(test)]
@@ -178,5 +175,15 @@ mod tests {
         assert_eq!(expanded.width, expected.width);
         assert_eq!(expanded.height, expected.height);
         assert_eq!(expanded.locations, expected.locations);
+        assert_eq!(expanded.to_string(), expected.to_string());
+    }
+
+    #[test]
+    fn test_display() {
+        let map: Map = "#..\n...\n..#\n".parse().unwrap();
+        assert_eq!(map.to_string(), "#..\n...\n..#\n");
+        let mut expanded = map.clone();
+        expanded.expand(3);
+        assert_eq!(expanded.to_string(), "#....\n.....\n.....\n.....\n....#\n");
     }
 }
@@ -0,0 +1,391 @@
+//! A dense, row-major 2D grid shared by the puzzles that work on character maps.
+use std::fmt;
+use std::ops::{Index, IndexMut};
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Point {
+    pub x: usize,
+    pub y: usize,
+}
+
+impl Point {
+    pub fn new(x: usize, y: usize) -> Self {
+        Point { x, y }
+    }
+
+    /// The point one step away in `direction`, or `None` if it would leave the
+    /// non-negative quadrant. No upper bound is checked.
+    pub fn step(&self, direction: Direction) -> Option<Point> {
+        self.offset(direction.delta())
+    }
+
+    pub fn offset(&self, (dx, dy): (isize, isize)) -> Option<Point> {
+        Some(Point {
+            x: self.x.checked_add_signed(dx)?,
+            y: self.y.checked_add_signed(dy)?,
+        })
+    }
+
+    pub fn manhattan(&self, other: Point) -> usize {
+        self.x.abs_diff(other.x) + self.y.abs_diff(other.y)
+    }
+}
+
+impl fmt::Debug for Point {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Point({},{})", self.x, self.y)
+    }
+}
+
+impl From<(usize, usize)> for Point {
+    fn from((x, y): (usize, usize)) -> Self {
+        Point { x, y }
+    }
+}
+
+impl From<Point> for (usize, usize) {
+    fn from(p: Point) -> Self {
+        (p.x, p.y)
+    }
+}
+
+/// Compass directions with north pointing towards row 0.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    N,
+    S,
+    E,
+    W,
+    NE,
+    NW,
+    SE,
+    SW,
+}
+
+impl Direction {
+    pub const CARDINAL: [Direction; 4] = [Direction::N, Direction::S, Direction::E, Direction::W];
+    pub const ALL: [Direction; 8] = [
+        Direction::NW,
+        Direction::N,
+        Direction::NE,
+        Direction::W,
+        Direction::E,
+        Direction::SW,
+        Direction::S,
+        Direction::SE,
+    ];
+
+    /// The `(dx, dy)` of a single step.
+    pub fn delta(&self) -> (isize, isize) {
+        match self {
+            Direction::N => (0, -1),
+            Direction::S => (0, 1),
+            Direction::E => (1, 0),
+            Direction::W => (-1, 0),
+            Direction::NE => (1, -1),
+            Direction::NW => (-1, -1),
+            Direction::SE => (1, 1),
+            Direction::SW => (-1, 1),
+        }
+    }
+
+    pub fn opposite(&self) -> Direction {
+        match self {
+            Direction::N => Direction::S,
+            Direction::S => Direction::N,
+            Direction::E => Direction::W,
+            Direction::W => Direction::E,
+            Direction::NE => Direction::SW,
+            Direction::NW => Direction::SE,
+            Direction::SE => Direction::NW,
+            Direction::SW => Direction::NE,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T: Clone> Grid<T> {
+    pub fn new(width: usize, height: usize, fill: T) -> Self {
+        Grid { width, height, cells: vec![fill; width * height] }
+    }
+
+    pub fn transpose(&self) -> Self {
+        let cells = (0..self.width).flat_map(|x| self.column(x).cloned()).collect();
+        Grid { width: self.height, height: self.width, cells }
+    }
+
+    /// Rotate a quarter turn clockwise.
+    pub fn rotate_cw(&self) -> Self {
+        let cells = (0..self.width)
+            .flat_map(|x| self.column(x).rev().cloned())
+            .collect();
+        Grid { width: self.height, height: self.width, cells }
+    }
+
+    /// Rotate a quarter turn counter-clockwise.
+    pub fn rotate_ccw(&self) -> Self {
+        let cells = (0..self.width)
+            .rev()
+            .flat_map(|x| self.column(x).cloned())
+            .collect();
+        Grid { width: self.height, height: self.width, cells }
+    }
+
+    pub fn rotate_180(&self) -> Self {
+        let cells = self.cells.iter().rev().cloned().collect();
+        Grid { width: self.width, height: self.height, cells }
+    }
+}
+
+impl<T> Grid<T> {
+    /// Build a grid from row-major cells. Fails unless the cells fill whole rows.
+    pub fn from_vec(width: usize, cells: Vec<T>) -> anyhow::Result<Self> {
+        if width == 0 {
+            if !cells.is_empty() {
+                anyhow::bail!("A grid with {} cells cannot have a width of 0", cells.len());
+            }
+            return Ok(Grid { width, height: 0, cells });
+        }
+        if !cells.len().is_multiple_of(width) {
+            anyhow::bail!("{} cells do not fill rows of width {width}", cells.len());
+        }
+        Ok(Grid { width, height: cells.len() / width, cells })
+    }
+
+    /// Parse one cell per character, one row per line. Empty lines at the end are
+    /// skipped, and every other line is a row with the same number of characters,
+    /// so a blank line in the middle is an error.
+    pub fn parse<F>(s: &str, mut f: F) -> anyhow::Result<Self>
+    where
+        F: FnMut(Point, char) -> anyhow::Result<T>,
+    {
+        let mut width = None;
+        let mut cells = Vec::new();
+        let lines: Vec<&str> = s.lines().collect();
+        let rows = lines.iter().rposition(|line| !line.is_empty()).map_or(0, |i| i + 1);
+        for (y, line) in lines[..rows].iter().enumerate() {
+            if line.is_empty() {
+                anyhow::bail!("Row {y} is blank");
+            }
+            let mut x = 0;
+            for c in line.chars() {
+                cells.push(f(Point { x, y }, c)?);
+                x += 1;
+            }
+            match width {
+                None => width = Some(x),
+                Some(width) if width != x => {
+                    anyhow::bail!("Row {y} has {x} cells but the rows above have {width}");
+                }
+                Some(_) => {}
+            }
+        }
+        Grid::from_vec(width.unwrap_or(0), cells)
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    pub fn contains(&self, point: Point) -> bool {
+        point.x < self.width && point.y < self.height
+    }
+
+    pub fn get(&self, point: Point) -> Option<&T> {
+        self.index_of(point).map(|i| &self.cells[i])
+    }
+
+    pub fn get_mut(&mut self, point: Point) -> Option<&mut T> {
+        self.index_of(point).map(|i| &mut self.cells[i])
+    }
+
+    /// Position of `point` in the row-major cell list, if it is on the grid.
+    pub fn index_of(&self, point: Point) -> Option<usize> {
+        self.contains(point).then(|| point.y * self.width + point.x)
+    }
+
+    pub fn point_of(&self, index: usize) -> Option<Point> {
+        (index < self.cells.len()).then(|| Point {
+            x: index % self.width,
+            y: index / self.width,
+        })
+    }
+
+    /// All cells in row-major order.
+    pub fn cells(&self) -> &[T] {
+        &self.cells
+    }
+
+    /// Every point on the grid in row-major order.
+    pub fn points(&self) -> impl Iterator<Item = Point> {
+        let width = self.width;
+        (0..self.height).flat_map(move |y| (0..width).map(move |x| Point { x, y }))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Point, &T)> {
+        self.points().zip(self.cells.iter())
+    }
+
+    pub fn row(&self, y: usize) -> &[T] {
+        &self.cells[y * self.width..(y + 1) * self.width]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        (0..self.height).map(|y| self.row(y))
+    }
+
+    pub fn column(&self, x: usize) -> impl DoubleEndedIterator<Item = &T> {
+        self.cells.iter().skip(x).step_by(self.width.max(1)).take(self.height)
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = impl DoubleEndedIterator<Item = &T>> {
+        (0..self.width).map(|x| self.column(x))
+    }
+
+    /// The neighbor of `point` in `direction` if it is on the grid.
+    pub fn step(&self, point: Point, direction: Direction) -> Option<Point> {
+        point.step(direction).filter(|p| self.contains(*p))
+    }
+
+    /// The up to four orthogonally adjacent points, in N, S, E, W order.
+    pub fn neighbors4(&self, point: Point) -> impl Iterator<Item = Point> + '_ {
+        Direction::CARDINAL
+            .into_iter()
+            .filter_map(move |d| self.step(point, d))
+    }
+
+    /// The up to eight surrounding points in row-major order.
+    pub fn neighbors8(&self, point: Point) -> impl Iterator<Item = Point> + '_ {
+        Direction::ALL
+            .into_iter()
+            .filter_map(move |d| self.step(point, d))
+    }
+
+    pub fn map<U, F: FnMut(&T) -> U>(&self, f: F) -> Grid<U> {
+        Grid {
+            width: self.width,
+            height: self.height,
+            cells: self.cells.iter().map(f).collect(),
+        }
+    }
+}
+
+impl<T> Index<Point> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, point: Point) -> &T {
+        self.get(point)
+            .unwrap_or_else(|| panic!("{point:?} is outside of a {}x{} grid", self.width, self.height))
+    }
+}
+
+impl<T> IndexMut<Point> for Grid<T> {
+    fn index_mut(&mut self, point: Point) -> &mut T {
+        let (width, height) = (self.width, self.height);
+        self.get_mut(point)
+            .unwrap_or_else(|| panic!("{point:?} is outside of a {width}x{height} grid"))
+    }
+}
+
+/// Rows are written on separate lines with no trailing newline.
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (y, row) in self.rows().enumerate() {
+            if y > 0 {
+                writeln!(f)?;
+            }
+            for cell in row {
+                write!(f, "{cell}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.rows()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[allow(unused_imports)]
+    use super::*;
+
+    fn chars(s: &str) -> Grid<char> {
+        Grid::parse(s, |_, c| Ok(c)).unwrap()
+    }
+
+    #[test]
+    fn test_parse_and_display() {
+        let grid = chars("abc\ndef\n");
+        assert_eq!(grid.width(), 3);
+        assert_eq!(grid.height(), 2);
+        assert_eq!(grid[Point::new(1, 1)], 'e');
+        assert_eq!(grid.get(Point::new(3, 0)), None);
+        assert_eq!(grid.to_string(), "abc\ndef");
+        assert!(Grid::parse("abc\nde", |_, c| Ok(c)).is_err());
+    }
+
+    #[test]
+    fn test_parse_blank_lines() {
+        let grid = Grid::parse("ab\ncd\n\n\n", |point, c| Ok((point, c))).unwrap();
+        assert_eq!(grid.height(), 2);
+        assert_eq!(grid[Point::new(1, 1)], (Point::new(1, 1), 'd'));
+        // Rows of spaces are cells like any other.
+        assert_eq!(chars("  \nab").height(), 2);
+        let err = Grid::parse("ab\n\ncd", |_, c| Ok(c)).unwrap_err();
+        assert_eq!(err.to_string(), "Row 1 is blank");
+        let err = Grid::parse("ab\ncd\nabc", |_, c| Ok(c)).unwrap_err();
+        assert_eq!(err.to_string(), "Row 2 has 3 cells but the rows above have 2");
+    }
+
+    #[test]
+    fn test_rows_and_columns() {
+        let grid = chars("abc\ndef");
+        assert_eq!(grid.row(1), &['d', 'e', 'f']);
+        let columns: Vec<String> = grid.columns().map(|c| c.collect()).collect();
+        assert_eq!(columns, vec!["ad", "be", "cf"]);
+    }
+
+    #[test]
+    fn test_neighbors() {
+        let grid = chars("abc\ndef\nghi");
+        let corner: Vec<char> = grid.neighbors8(Point::new(0, 0)).map(|p| grid[p]).collect();
+        assert_eq!(corner, vec!['b', 'd', 'e']);
+        let center: Vec<char> = grid.neighbors8(Point::new(1, 1)).map(|p| grid[p]).collect();
+        assert_eq!(center, vec!['a', 'b', 'c', 'd', 'f', 'g', 'h', 'i']);
+        let edge: Vec<char> = grid.neighbors4(Point::new(1, 0)).map(|p| grid[p]).collect();
+        assert_eq!(edge, vec!['e', 'c', 'a']);
+    }
+
+    #[test]
+    fn test_rotations() {
+        let grid = chars("abc\ndef");
+        assert_eq!(grid.transpose().to_string(), "ad\nbe\ncf");
+        assert_eq!(grid.rotate_cw().to_string(), "da\neb\nfc");
+        assert_eq!(grid.rotate_ccw().to_string(), "cf\nbe\nad");
+        assert_eq!(grid.rotate_180().to_string(), "fed\ncba");
+        assert_eq!(grid.rotate_cw().rotate_cw(), grid.rotate_180());
+        assert_eq!(grid.rotate_cw().rotate_ccw(), grid);
+    }
+}
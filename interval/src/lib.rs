@@ -0,0 +1,61 @@
+//! Sets and maps of half-open integer intervals, shared by the puzzles that
+//! work on large ranges of numbers.
+use std::fmt::Debug;
+use std::ops::{Add, Range, Sub};
+
+mod map;
+mod set;
+
+pub use map::IntervalMap;
+pub use set::IntervalSet;
+
+/// An integer type that can bound an interval.
+pub trait Endpoint: Copy + Ord + Debug + Add<Output = Self> + Sub<Output = Self> {}
+
+macro_rules! endpoint {
+    ($($t:ty),*) => {
+        $(impl Endpoint for $t {})*
+    };
+}
+
+endpoint!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+/// A value attached to an interval that changes along it. Looking up a point
+/// `by` past the start of an interval gives `value.offset(by)`.
+pub trait Offset<T> {
+    fn offset(&self, by: T) -> Self;
+}
+
+/// Integers move along with the point, so an interval starting at `s` with
+/// value `v` maps `s + d` to `v + d`.
+impl<T: Endpoint> Offset<T> for T {
+    fn offset(&self, by: T) -> Self {
+        *self + by
+    }
+}
+
+/// Every range where two of `ranges` overlap, once per overlapping pair.
+pub fn overlaps<T: Endpoint>(ranges: impl IntoIterator<Item = Range<T>>) -> Vec<Range<T>> {
+    let mut ranges: Vec<Range<T>> = ranges.into_iter().filter(|r| r.start < r.end).collect();
+    ranges.sort_by_key(|range| range.start);
+    let mut found = Vec::new();
+    for (i, a) in ranges.iter().enumerate() {
+        for b in ranges[i + 1..].iter().take_while(|b| b.start < a.end) {
+            found.push(b.start..a.end.min(b.end));
+        }
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    #[allow(unused_imports)]
+    use super::*;
+
+    #[test]
+    fn test_overlaps() {
+        assert_eq!(overlaps([0..10, 5..15, 20..30, 8..9]), vec![5..10, 8..9, 8..9]);
+        assert_eq!(overlaps([0..5, 5..10, 3..3]), vec![]);
+        assert_eq!(overlaps([-5i32..0, -1..1]), vec![-1..0]);
+    }
+}
@@ -0,0 +1,210 @@
+use std::ops::Range;
+
+use crate::{overlaps, Endpoint, IntervalSet, Offset};
+
+/// Values attached to intervals, looked up with [`Offset`] semantics.
+///
+/// Intervals may overlap, in which case a point belongs to the containing
+/// interval that starts last. [`IntervalMap::overlaps`] reports where that
+/// happens so callers that want a function can reject it.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct IntervalMap<T, V> {
+    /// Sorted by start, ties in insertion order.
+    entries: Vec<(Range<T>, V)>,
+    /// The largest end of the entries up to and including each index.
+    reach: Vec<T>,
+}
+
+impl<T, V> Default for IntervalMap<T, V> {
+    fn default() -> Self {
+        IntervalMap { entries: Vec::new(), reach: Vec::new() }
+    }
+}
+
+impl<T: Endpoint, V> IntervalMap<T, V> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, range: Range<T>, value: V) {
+        let index = self.entries.partition_point(|(r, _)| r.start <= range.start);
+        self.entries.insert(index, (range, value));
+        self.reach.truncate(index);
+        for (range, _) in &self.entries[index..] {
+            let end = match self.reach.last() {
+                Some(reach) => range.end.max(*reach),
+                None => range.end,
+            };
+            self.reach.push(end);
+        }
+    }
+
+    /// The entries by start.
+    pub fn entries(&self) -> &[(Range<T>, V)] {
+        &self.entries
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The interval containing `point` and its value at the interval's start.
+    pub fn entry(&self, point: T) -> Option<&(Range<T>, V)> {
+        self.position(point).map(|i| &self.entries[i])
+    }
+
+    /// The index of the entry returned by [`IntervalMap::entry`].
+    fn position(&self, point: T) -> Option<usize> {
+        let candidates = self.entries.partition_point(|(r, _)| r.start <= point);
+        (0..candidates)
+            .rev()
+            .take_while(|i| self.reach[*i] > point)
+            .find(|i| point < self.entries[*i].0.end)
+    }
+
+    /// Every point covered by some interval.
+    pub fn domain(&self) -> IntervalSet<T> {
+        self.entries.iter().map(|(range, _)| range.clone()).collect()
+    }
+
+    /// Every range where two intervals overlap.
+    pub fn overlaps(&self) -> Vec<Range<T>> {
+        overlaps(self.entries.iter().map(|(range, _)| range.clone()))
+    }
+}
+
+impl<T: Endpoint, V: Offset<T>> IntervalMap<T, V> {
+    pub fn get(&self, point: T) -> Option<V> {
+        self.entry(point).map(|(range, value)| value.offset(point - range.start))
+    }
+
+    /// Split `range` at every interval boundary. Each piece comes with the value
+    /// at its start, or `None` where no interval covers it.
+    pub fn pieces(&self, range: Range<T>) -> Vec<(Range<T>, Option<V>)> {
+        if range.start >= range.end {
+            return Vec::new();
+        }
+        let mut bounds: Vec<T> = vec![range.start, range.end];
+        let first = self.entries.partition_point(|(r, _)| r.start < range.end);
+        for (r, _) in &self.entries[..first] {
+            for bound in [r.start, r.end] {
+                if range.start < bound && bound < range.end {
+                    bounds.push(bound);
+                }
+            }
+        }
+        bounds.sort();
+        bounds.dedup();
+        let mut pieces: Vec<(Range<T>, Option<usize>)> = Vec::new();
+        for pair in bounds.windows(2) {
+            let position = self.position(pair[0]);
+            match pieces.last_mut() {
+                Some((last, p)) if *p == position => last.end = pair[1],
+                _ => pieces.push((pair[0]..pair[1], position)),
+            }
+        }
+        pieces
+            .into_iter()
+            .map(|(piece, position)| {
+                let value = position.map(|i| {
+                    let (range, value) = &self.entries[i];
+                    value.offset(piece.start - range.start)
+                });
+                (piece, value)
+            })
+            .collect()
+    }
+}
+
+impl<T: Endpoint, V> FromIterator<(Range<T>, V)> for IntervalMap<T, V> {
+    fn from_iter<I: IntoIterator<Item = (Range<T>, V)>>(iter: I) -> Self {
+        let mut map = IntervalMap::new();
+        for (range, value) in iter {
+            map.insert(range, value);
+        }
+        map
+    }
+}
+
+#[cfg(test)]
+// Single ranges and empty ones are exactly what some cases are about.
+#[allow(clippy::single_range_in_vec_init)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    #[test]
+    fn test_get() {
+        let map: IntervalMap<u32, u32> = [(98..100, 50), (50..98, 52)].into_iter().collect();
+        assert_eq!(map.get(49), None);
+        assert_eq!(map.get(50), Some(52));
+        assert_eq!(map.get(97), Some(99));
+        assert_eq!(map.get(99), Some(51));
+        assert_eq!(map.get(100), None);
+        assert_eq!(map.domain().ranges(), &[50..100]);
+        assert_eq!(map.overlaps(), vec![]);
+    }
+
+    #[test]
+    fn test_overlapping() {
+        let map: IntervalMap<i64, i64> =
+            [(0..100, 1000), (10..20, -10), (30..40, 0)].into_iter().collect();
+        assert_eq!(map.get(15), Some(-5));
+        assert_eq!(map.get(25), Some(1025));
+        assert_eq!(map.get(99), Some(1099));
+        assert_eq!(map.overlaps(), vec![10..20, 30..40]);
+    }
+
+    #[test]
+    fn test_pieces() {
+        let map: IntervalMap<u32, u32> = [(98..100, 50), (50..98, 52)].into_iter().collect();
+        assert_eq!(
+            map.pieces(40..99),
+            vec![(40..50, None), (50..98, Some(52)), (98..99, Some(50))],
+        );
+        assert_eq!(map.pieces(60..70), vec![(60..70, Some(62))]);
+        assert_eq!(map.pieces(5..5), vec![]);
+    }
+
+    fn entries() -> impl Strategy<Value = Vec<(Range<u16>, u16)>> {
+        prop::collection::vec((0..200u16, 0..50u16, 0..1000u16), 0..8)
+            .prop_map(|v| v.into_iter().map(|(s, l, d)| (s..s + l, d)).collect())
+    }
+
+    proptest! {
+        #[test]
+        fn test_matches_model(entries in entries(), start in 0..260u16, len in 0..60u16) {
+            let map: IntervalMap<u16, u16> = entries.iter().cloned().collect();
+            // The model: the containing entry with the latest start, the last
+            // inserted among equal starts.
+            let model = |p: u16| {
+                entries
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, (r, _))| r.contains(&p))
+                    .max_by_key(|(i, (r, _))| (r.start, *i))
+                    .map(|(_, (r, d))| d + (p - r.start))
+            };
+            for p in 0..260 {
+                prop_assert_eq!(map.get(p), model(p));
+            }
+            let range = start..start + len;
+            let pieces = map.pieces(range.clone());
+            let mut next = range.start;
+            for (piece, value) in &pieces {
+                prop_assert_eq!(piece.start, next);
+                prop_assert!(piece.start < piece.end);
+                next = piece.end;
+                for p in piece.clone() {
+                    prop_assert_eq!(value.map(|v| v + (p - piece.start)), model(p));
+                }
+            }
+            prop_assert_eq!(next, range.end);
+        }
+    }
+}
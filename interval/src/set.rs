@@ -0,0 +1,225 @@
+use std::ops::Range;
+
+use crate::Endpoint;
+
+/// A set of integers stored as sorted, disjoint, non-adjacent ranges.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct IntervalSet<T> {
+    ranges: Vec<Range<T>>,
+}
+
+impl<T> Default for IntervalSet<T> {
+    fn default() -> Self {
+        IntervalSet { ranges: Vec::new() }
+    }
+}
+
+impl<T: Endpoint> IntervalSet<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sort `ranges` and merge the ones that overlap or touch, dropping empty ones.
+    pub fn normalize(mut ranges: Vec<Range<T>>) -> Vec<Range<T>> {
+        ranges.retain(|range| range.start < range.end);
+        ranges.sort_by_key(|range| range.start);
+        let mut merged: Vec<Range<T>> = Vec::with_capacity(ranges.len());
+        for range in ranges {
+            match merged.last_mut() {
+                Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+                _ => merged.push(range),
+            }
+        }
+        merged
+    }
+
+    pub fn insert(&mut self, range: Range<T>) {
+        let mut ranges = std::mem::take(&mut self.ranges);
+        ranges.push(range);
+        self.ranges = Self::normalize(ranges);
+    }
+
+    pub fn contains(&self, value: T) -> bool {
+        let i = self.ranges.partition_point(|range| range.end <= value);
+        self.ranges.get(i).is_some_and(|range| range.start <= value)
+    }
+
+    /// Whether any value of `range` is in the set.
+    pub fn intersects(&self, range: &Range<T>) -> bool {
+        let i = self.ranges.partition_point(|r| r.end <= range.start);
+        self.ranges
+            .get(i)
+            .is_some_and(|r| r.start < range.end && range.start < range.end)
+    }
+
+    pub fn ranges(&self) -> &[Range<T>] {
+        &self.ranges
+    }
+
+    pub fn into_ranges(self) -> Vec<Range<T>> {
+        self.ranges
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Range<T>> {
+        self.ranges.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// The smallest value in the set.
+    pub fn first(&self) -> Option<T> {
+        self.ranges.first().map(|range| range.start)
+    }
+
+    pub fn union(&self, other: &IntervalSet<T>) -> IntervalSet<T> {
+        self.ranges.iter().chain(other.ranges.iter()).cloned().collect()
+    }
+
+    pub fn intersection(&self, other: &IntervalSet<T>) -> IntervalSet<T> {
+        let (mut i, mut j) = (0, 0);
+        let mut ranges = Vec::new();
+        while i < self.ranges.len() && j < other.ranges.len() {
+            let (a, b) = (&self.ranges[i], &other.ranges[j]);
+            let start = a.start.max(b.start);
+            let end = a.end.min(b.end);
+            if start < end {
+                ranges.push(start..end);
+            }
+            if a.end < b.end {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        // Pieces of disjoint, sorted ranges are already disjoint and sorted.
+        IntervalSet { ranges }
+    }
+
+    /// Values in `self` but not in `other`.
+    pub fn difference(&self, other: &IntervalSet<T>) -> IntervalSet<T> {
+        let Some(hull) = self.hull() else {
+            return IntervalSet::new();
+        };
+        self.intersection(&other.complement(hull))
+    }
+
+    /// Values within `bounds` that are not in the set.
+    pub fn complement(&self, bounds: Range<T>) -> IntervalSet<T> {
+        let mut ranges = Vec::new();
+        let mut start = bounds.start;
+        for range in &self.ranges {
+            if range.end <= start {
+                continue;
+            }
+            if range.start >= bounds.end {
+                break;
+            }
+            if range.start > start {
+                ranges.push(start..range.start);
+            }
+            start = range.end;
+        }
+        if start < bounds.end {
+            ranges.push(start..bounds.end);
+        }
+        IntervalSet { ranges }
+    }
+
+    /// The smallest range containing the whole set.
+    pub fn hull(&self) -> Option<Range<T>> {
+        Some(self.ranges.first()?.start..self.ranges.last()?.end)
+    }
+}
+
+impl<T: Endpoint> FromIterator<Range<T>> for IntervalSet<T> {
+    fn from_iter<I: IntoIterator<Item = Range<T>>>(iter: I) -> Self {
+        IntervalSet { ranges: Self::normalize(iter.into_iter().collect()) }
+    }
+}
+
+impl<T: Endpoint> From<Vec<Range<T>>> for IntervalSet<T> {
+    fn from(ranges: Vec<Range<T>>) -> Self {
+        IntervalSet { ranges: Self::normalize(ranges) }
+    }
+}
+
+#[cfg(test)]
+// Single ranges and empty ones are exactly what some cases are about.
+#[allow(clippy::single_range_in_vec_init, clippy::reversed_empty_ranges)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use proptest::prelude::*;
+
+    use super::*;
+
+    fn set(ranges: &[Range<i32>]) -> IntervalSet<i32> {
+        ranges.iter().cloned().collect()
+    }
+
+    #[test]
+    fn test_normalize() {
+        let s = set(&[5..8, 0..2, 1..3, 8..9, 4..4, 12..11]);
+        assert_eq!(s.ranges(), &[0..3, 5..9]);
+        assert!(s.contains(2));
+        assert!(!s.contains(3));
+        assert!(s.intersects(&(2..6)));
+        assert!(!s.intersects(&(3..5)));
+    }
+
+    #[test]
+    fn test_operations() {
+        let a = set(&[0..10, 20..30]);
+        let b = set(&[5..25]);
+        assert_eq!(a.union(&b).ranges(), &[0..30]);
+        assert_eq!(a.intersection(&b).ranges(), &[5..10, 20..25]);
+        assert_eq!(a.difference(&b).ranges(), &[0..5, 25..30]);
+        assert_eq!(a.complement(-5..40).ranges(), &[-5..0, 10..20, 30..40]);
+        assert_eq!(a.complement(2..8).ranges(), &[] as &[Range<i32>]);
+    }
+
+    #[test]
+    fn test_full_range() {
+        let s: IntervalSet<u8> = [0..u8::MAX].into_iter().collect();
+        assert_eq!(s.complement(0..u8::MAX), IntervalSet::new());
+        assert!(s.contains(254));
+        assert!(!s.contains(255));
+    }
+
+    fn ranges() -> impl Strategy<Value = Vec<Range<u8>>> {
+        prop::collection::vec((0..200u8, 0..40u8).prop_map(|(s, l)| s..s + l), 0..6)
+    }
+
+    fn model(ranges: &[Range<u8>]) -> BTreeSet<u8> {
+        ranges.iter().flat_map(|r| r.clone()).collect()
+    }
+
+    fn values(set: &IntervalSet<u8>) -> BTreeSet<u8> {
+        model(set.ranges())
+    }
+
+    proptest! {
+        #[test]
+        fn test_matches_model(a in ranges(), b in ranges(), lo in 0..250u8, len in 0..100u8) {
+            let (sa, sb) = (IntervalSet::from(a.clone()), IntervalSet::from(b.clone()));
+            let (ma, mb) = (model(&a), model(&b));
+            // Normalized: sorted, non-empty, not touching.
+            for pair in sa.ranges().windows(2) {
+                prop_assert!(pair[0].end < pair[1].start);
+            }
+            prop_assert!(sa.ranges().iter().all(|r| r.start < r.end));
+            prop_assert_eq!(values(&sa), ma.clone());
+            prop_assert_eq!(values(&sa.union(&sb)), &ma | &mb);
+            prop_assert_eq!(values(&sa.intersection(&sb)), &ma & &mb);
+            prop_assert_eq!(values(&sa.difference(&sb)), &ma - &mb);
+            let bounds = lo..lo.saturating_add(len);
+            let inside: BTreeSet<u8> = bounds.clone().filter(|v| !ma.contains(v)).collect();
+            prop_assert_eq!(values(&sa.complement(bounds)), inside);
+            for v in 0..=255u8 {
+                prop_assert_eq!(sa.contains(v), ma.contains(&v));
+            }
+        }
+    }
+}